@@ -0,0 +1,20 @@
+/*
+ * Steps-per-second on a randomized 256x256 life board, with no window or rendering
+ */
+use conbhuide::life::CellMatrix;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const BOARD_SIZE: u16 = 256;
+
+fn bench_step(c: &mut Criterion) {
+    let mut cell_matrix = CellMatrix::with_dimensions(BOARD_SIZE, BOARD_SIZE, 1, None);
+    cell_matrix.randomize(Some(0.3));
+
+    let mut group = c.benchmark_group("life");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("step 256x256", |b| b.iter(|| cell_matrix.step()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_step);
+criterion_main!(benches);
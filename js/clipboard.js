@@ -0,0 +1,14 @@
+// Lets the wasm build copy text with the browser's clipboard API, when built with the web
+// feature (see copy_to_clipboard in src/main.rs)
+miniquad_add_plugin({
+    register_plugin: function (importObject) {
+        importObject.env.conbhuide_copy = function (text_ptr, text_len) {
+            var text = UTF8ToString(text_ptr, text_len);
+            navigator.clipboard.writeText(text).catch(function (e) {
+                console.warn("Couldn't copy to the clipboard: " + e);
+            });
+        };
+    },
+    name: "conbhuide_copy",
+    version: 1
+});
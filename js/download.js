@@ -0,0 +1,18 @@
+// Lets the wasm build hand files to the browser to download (see save_file in src/main.rs)
+miniquad_add_plugin({
+    register_plugin: function (importObject) {
+        importObject.env.conbhuide_download = function (name_ptr, name_len, bytes_ptr, bytes_len) {
+            var name = UTF8ToString(name_ptr, name_len);
+            // copy the bytes out, as wasm memory can move when it grows
+            var bytes = new Uint8Array(wasm_memory.buffer, bytes_ptr, bytes_len).slice();
+            var url = URL.createObjectURL(new Blob([bytes]));
+            var link = document.createElement("a");
+            link.href = url;
+            link.download = name;
+            link.click();
+            URL.revokeObjectURL(url);
+        };
+    },
+    name: "conbhuide_download",
+    version: 1
+});
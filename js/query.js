@@ -0,0 +1,19 @@
+// Lets the wasm build read the page's query string, for startup options like ?pattern=glider
+// (see page_query in src/main.rs)
+function conbhuide_query_bytes() {
+    return new TextEncoder().encode(decodeURIComponent(window.location.search));
+}
+
+miniquad_add_plugin({
+    register_plugin: function (importObject) {
+        importObject.env.conbhuide_query_len = function () {
+            return conbhuide_query_bytes().length;
+        };
+        importObject.env.conbhuide_query = function (bytes_ptr, bytes_len) {
+            var bytes = conbhuide_query_bytes().subarray(0, bytes_len);
+            new Uint8Array(wasm_memory.buffer, bytes_ptr, bytes_len).set(bytes);
+        };
+    },
+    name: "conbhuide_query",
+    version: 1
+});
@@ -0,0 +1,197 @@
+/*
+ * The frontend's state and the commands that change it.
+ *
+ * Key presses and clicks in main.rs are turned into Commands and handed to AppState::apply,
+ * so anything the keyboard can do can also be done by feeding in a list of commands, without
+ * opening a window.
+ */
+use crate::edge::TileMatrix;
+use crate::life::{CellMatrix, PaintMode};
+use crate::palette::Theme;
+use crate::patterns::PatternRegistry;
+use crate::rle::Orientation;
+use macroquad::logging::info;
+
+// seconds an empty board is left showing before auto_reseed_on_death brings it back
+pub const RESEED_DELAY: f64 = 1.5;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DrawMode {
+    // a click flips the cell under it (or its wall, when painting walls)
+    #[default]
+    Paint,
+    // a click only ever kills cells (or takes walls down)
+    Erase,
+    // a click stamps the latest saved pattern with its top left there
+    Stamp,
+    // dragging picks out a rectangle to save as a pattern, or with shift to randomize
+    Select,
+}
+
+impl DrawMode {
+    pub fn next(self) -> Self {
+        match self {
+            DrawMode::Paint => DrawMode::Erase,
+            DrawMode::Erase => DrawMode::Stamp,
+            DrawMode::Stamp => DrawMode::Select,
+            DrawMode::Select => DrawMode::Paint,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    // pause or resume stepping the life board
+    TogglePlay,
+    // evolve the life board one generation
+    Step,
+    // reseed whichever board takes input, at its density
+    Randomize,
+    // empty whichever board takes input
+    Clear,
+    // move input between the life board and the knot
+    SwitchMode,
+    // step the life board back a generation, or take back the latest edit to the knot
+    Undo,
+    // recolor both boards with the next theme
+    NextTheme,
+    // change what a click on the life board does
+    SetDrawMode(DrawMode),
+    // a click on life cell x,y, done as the draw mode says
+    ClickCell(u16, u16),
+}
+
+pub struct AppState {
+    pub cell_matrix: CellMatrix,
+    pub tile_matrix: TileMatrix,
+    // whether the life board steps by itself
+    pub running: bool,
+    // true when the knot takes input, false for the life board
+    pub is_conway: bool,
+    // chance of each cell coming alive, and of each edge appearing, when randomizing
+    pub density: f32,
+    pub edge_density: f32,
+    // patterns saved from the life board, for stamping again
+    pub patterns: PatternRegistry,
+    pub theme: Theme,
+    // what clicks on the life board do
+    pub draw_mode: DrawMode,
+    // how saved patterns are turned before stamping them
+    pub stamp_orientation: Orientation,
+    // reseed the life board at density once it has died out, e.g. when left running as a display
+    pub auto_reseed_on_death: bool,
+    // time the life board was first seen empty, while waiting to reseed it
+    died_at: Option<f64>,
+}
+
+impl AppState {
+    pub fn new(cell_matrix: CellMatrix, tile_matrix: TileMatrix) -> Self {
+        let density = cell_matrix.density();
+        AppState {
+            cell_matrix,
+            tile_matrix,
+            running: true,
+            is_conway: true,
+            density,
+            edge_density: 0.3,
+            patterns: PatternRegistry::default(),
+            theme: Theme::default(),
+            draw_mode: DrawMode::default(),
+            stamp_orientation: Orientation::default(),
+            auto_reseed_on_death: false,
+            died_at: None,
+        }
+    }
+
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::TogglePlay => {
+                self.running = !self.running;
+                if self.running {
+                    info!("Resumed");
+                } else {
+                    info!("Paused");
+                }
+            }
+            Command::Step => self.cell_matrix.step(),
+            Command::Randomize if self.is_conway => {
+                self.tile_matrix.randomize_edges(self.edge_density);
+                info!("Randomized edges at density {}", self.edge_density);
+            }
+            Command::Randomize => {
+                self.cell_matrix.randomize(Some(self.density));
+                info!("Reseeded at density {}", self.density);
+            }
+            Command::Clear if self.is_conway => self.tile_matrix.clear_edges(),
+            Command::Clear => self.cell_matrix.clear(),
+            Command::SwitchMode => self.is_conway = !self.is_conway,
+            Command::Undo if self.is_conway => {
+                if !self.tile_matrix.undo() {
+                    info!("No edit to undo");
+                }
+            }
+            Command::Undo => {
+                if !self.cell_matrix.step_back() {
+                    info!("No earlier generation recorded");
+                }
+            }
+            Command::NextTheme => {
+                self.theme = self.theme.next();
+                let palette = self.theme.palette();
+                self.cell_matrix.apply_palette(&palette);
+                self.tile_matrix.apply_palette(&palette);
+                info!("Theme: {:?}", self.theme);
+            }
+            Command::SetDrawMode(draw_mode) => {
+                self.draw_mode = draw_mode;
+                info!("Draw mode: {:?}", draw_mode);
+            }
+            Command::ClickCell(x, y) if x >= self.cell_matrix.width || y >= self.cell_matrix.height => {}
+            Command::ClickCell(x, y) => match self.draw_mode {
+                DrawMode::Paint => self.cell_matrix.flip_cell_xy(x, y),
+                DrawMode::Erase if self.cell_matrix.paint_mode == PaintMode::Walls => {
+                    self.cell_matrix.set_wall_xy(x, y, false)
+                }
+                DrawMode::Erase => self.cell_matrix.set_cell_xy(x, y, false),
+                DrawMode::Stamp => self.stamp_latest(x, y),
+                // selecting is a drag, which main.rs follows
+                DrawMode::Select => {}
+            },
+        }
+    }
+
+    pub fn stamp_latest(&mut self, x: u16, y: u16) {
+        /* stamp the latest saved pattern, turned to stamp_orientation, with its top left at x,y */
+        match self.patterns.latest() {
+            Some((name, pattern)) => {
+                self.cell_matrix.stamp_oriented(pattern, x, y, self.stamp_orientation);
+                info!("Stamped {} at {},{} ({:?})", name, x, y, self.stamp_orientation);
+            }
+            None => info!("No saved patterns; select part of the board to save one"),
+        }
+    }
+
+    pub fn reseed_if_dead(&mut self, now: f64) -> bool {
+        /* with auto_reseed_on_death on, reseed the life board at density once it has been
+         * empty for RESEED_DELAY seconds, so the death is seen first. Call after stepping, with
+         * the time in seconds; returns whether it reseeded */
+        if !self.auto_reseed_on_death || self.cell_matrix.population() > 0 {
+            self.died_at = None;
+            return false;
+        }
+        let died_at = *self.died_at.get_or_insert(now);
+        if now - died_at < RESEED_DELAY {
+            return false;
+        }
+        self.died_at = None;
+        info!("Board died out at generation {}; reseeding at density {}", self.cell_matrix.generation(), self.density);
+        self.cell_matrix.randomize(Some(self.density));
+        true
+    }
+
+    pub fn apply_all(&mut self, commands: &[Command]) {
+        for &command in commands {
+            self.apply(command);
+        }
+    }
+}
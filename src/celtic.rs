@@ -7,8 +7,10 @@
 * expose anything more than lines, rectangles, and circles
 *
 */
+use crate::error::ConbhuideError;
 use macroquad::prelude::*;
 use phf::{phf_map, Map};
+use std::collections::HashMap;
 use std::f32::consts::PI;
 
 static TILE_LOCS: Map<&'static str, (u16, u16)> = phf_map! {
@@ -19,6 +21,34 @@ static TILE_LOCS: Map<&'static str, (u16, u16)> = phf_map! {
     "curved_cross_under" => (8,0),
 };
 
+// the bundled atlas, built into the binary
+#[cfg(feature = "embedded-texture")]
+pub static EMBEDDED_KNOTS_PNG: &[u8] = include_bytes!("../img/knots.png");
+
+#[cfg(feature = "embedded-texture")]
+pub fn embedded_texture() -> Texture2D {
+    /* the bundled img/knots.png as a texture, without touching the filesystem */
+    Texture2D::from_file_with_format(EMBEDDED_KNOTS_PNG, Some(ImageFormat::Png))
+}
+
+// where each of the drawings sits in an atlas, in tiles from its top left
+pub type TileLocs = HashMap<String, (u16, u16)>;
+
+pub fn default_tile_locs() -> TileLocs {
+    /* locations of the drawings in the bundled img/knots.png */
+    TILE_LOCS.entries().map(|(name, loc)| (name.to_string(), *loc)).collect()
+}
+
+pub fn check_tile_locs(tile_locs: &TileLocs) -> Result<(), ConbhuideError> {
+    /* an atlas must say where to find every drawing the tiles use */
+    let mut missing: Vec<&str> = TILE_LOCS.keys().copied().filter(|name| !tile_locs.contains_key(*name)).collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    missing.sort_unstable();
+    Err(ConbhuideError::AssetLoad(format!("Tile map is missing {}", missing.join(", "))))
+}
+
 #[derive(Clone, Debug)]
 pub enum Cut {
     Open,
@@ -41,15 +71,28 @@ pub struct Tile {
     pub col_offset: Offset,
 }
 
-fn draw_tile(
-    texture: &Texture2D,
-    top_left: Vec2,
-    loc: (u16, u16),
-    rotation: f32,
-    tile_size: u16,
-    flip_x: bool,
-    flip_y: bool,
-) {
+// colors to multiply the two halves of a tile by, to show the weave (see TileMatrix::weave_tints)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HalfTints {
+    // for the half of the tile around its top crossing corner, and around its bottom one
+    pub top: Color,
+    pub bottom: Color,
+    // the top crossing corner is the tile's top right, i.e. the strand runs down and to the left
+    pub top_right: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TilePlacement {
+    // which drawing in the atlas
+    pub drawing: &'static str,
+    // clockwise, applied after flipping
+    pub rotation: f32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+fn draw_tile(texture: &Texture2D, tile_locs: &TileLocs, top_left: Vec2, placement: TilePlacement, tile_size: u16) {
+    let loc = tile_locs[placement.drawing];
     draw_texture_ex(
         texture,
         top_left.x,
@@ -62,20 +105,136 @@ fn draw_tile(
                 tile_size.into(),
                 tile_size.into(),
             )),
-            rotation,
-            flip_x,
-            flip_y,
+            rotation: placement.rotation,
+            flip_x: placement.flip_x,
+            flip_y: placement.flip_y,
             ..Default::default()
         },
     );
 }
 
-pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_size: u16) {
+fn draw_tile_halves(
+    texture: &Texture2D,
+    tile_locs: &TileLocs,
+    top_left: Vec2,
+    placement: TilePlacement,
+    tile_size: u16,
+    tints: HalfTints,
+) {
+    /* draw_tile as two triangles split across the strand, each tinted. The texture coordinates
+     * for each corner undo the rotation and flips as blit_tile does */
+    let loc = tile_locs[placement.drawing];
+    let quarter_turns = (placement.rotation / (PI / 2.0)).round() as i32;
+    let size = tile_size as f32;
+    let vertex = |corner: (f32, f32), color: Color| {
+        let (mut a, mut b) = corner;
+        for _ in 0..quarter_turns.rem_euclid(4) {
+            (a, b) = (b, 1.0 - a);
+        }
+        if placement.flip_x {
+            a = 1.0 - a;
+        }
+        if placement.flip_y {
+            b = 1.0 - b;
+        }
+        let u = (loc.0 as f32 + a) * size / texture.width();
+        let v = (loc.1 as f32 + b) * size / texture.height();
+        Vertex::new(top_left.x + corner.0 * size, top_left.y + corner.1 * size, 0.0, u, v, color)
+    };
+    // each half is the triangle between its crossing corner and the two corners off the strand
+    let (top, bottom, off) = if tints.top_right {
+        ((1.0, 0.0), (0.0, 1.0), [(0.0, 0.0), (1.0, 1.0)])
+    } else {
+        ((0.0, 0.0), (1.0, 1.0), [(1.0, 0.0), (0.0, 1.0)])
+    };
+    let vertices = vec![
+        vertex(top, tints.top),
+        vertex(off[0], tints.top),
+        vertex(off[1], tints.top),
+        vertex(bottom, tints.bottom),
+        vertex(off[0], tints.bottom),
+        vertex(off[1], tints.bottom),
+    ];
+    draw_mesh(&Mesh {
+        vertices,
+        indices: vec![0, 1, 2, 3, 4, 5],
+        texture: Some(texture.clone()),
+    });
+}
+
+pub fn blit_tile(
+    atlas: &Image,
+    tile_locs: &TileLocs,
+    placement: Option<TilePlacement>,
+    tile_size: u16,
+    target: &mut Image,
+    top_left: (u32, u32),
+) {
+    /* software version of draw_tile, copying a tile's pixels from the atlas into an image.
+     * Rotations are always quarter turns, so each target pixel maps to exactly one atlas pixel */
+    let size = tile_size as u32;
+    for v in 0..size {
+        for u in 0..size {
+            let (x, y) = (top_left.0 + u, top_left.1 + v);
+            if x >= target.width as u32 || y >= target.height as u32 {
+                continue;
+            }
+            let color = match placement {
+                Some(placement) => {
+                    // undo the rotation, then the flips
+                    let quarter_turns = (placement.rotation / (PI / 2.0)).round() as i32;
+                    let (mut a, mut b) = (u, v);
+                    for _ in 0..quarter_turns.rem_euclid(4) {
+                        (a, b) = (b, size - 1 - a);
+                    }
+                    if placement.flip_x {
+                        a = size - 1 - a;
+                    }
+                    if placement.flip_y {
+                        b = size - 1 - b;
+                    }
+                    let loc = tile_locs[placement.drawing];
+                    let (loc_x, loc_y) = (loc.0 as u32 * size, loc.1 as u32 * size);
+                    atlas.get_pixel(loc_x + a, loc_y + b)
+                }
+                None => BLACK,
+            };
+            target.set_pixel(x, y, color);
+        }
+    }
+}
+
+pub fn draw_expr_for_tile(
+    texture: &Texture2D,
+    tile_locs: &TileLocs,
+    tile: Tile,
+    top_left: Vec2,
+    tile_size: u16,
+    tints: Option<HalfTints>,
+) {
+    /* with tints, each half of the tile is multiplied by its color */
+    match (placement_for_tile(&tile), tints) {
+        (Some(placement), None) => draw_tile(texture, tile_locs, top_left, placement, tile_size),
+        (Some(placement), Some(tints)) => draw_tile_halves(texture, tile_locs, top_left, placement, tile_size, tints),
+        // error
+        (None, _) => draw_rectangle(
+            top_left.x,
+            top_left.y,
+            tile_size.into(),
+            tile_size.into(),
+            BLACK,
+        ),
+    }
+}
+
+pub fn placement_for_tile(tile: &Tile) -> Option<TilePlacement> {
     /*
      * There are 36 possible tiles in celtic knots,
      * which can be rendered using 5 drawings in various orientations
      *
      * TODO: there must be a more concise way to express this
+     *
+     * Anything else can't be drawn, and gives None
      */
     match tile {
         Tile {
@@ -85,15 +244,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //drawStraightCross
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["straight_cross"],
-                0.0,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "straight_cross",
+                rotation: 0.0,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -102,15 +258,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(drawStraightCross, 90)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["straight_cross"],
-                PI / 2.0,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "straight_cross",
+                rotation: PI / 2.0,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -119,15 +272,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(drawStraightCross, 180)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["straight_cross"],
-                PI,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "straight_cross",
+                rotation: PI,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -136,15 +286,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"rotate(drawStraightCross, 270)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["straight_cross"],
-                PI * 1.5,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "straight_cross",
+                rotation: PI * 1.5,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Horizontal,
@@ -159,15 +306,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //drawCorner NB: the corner tile I'm using is upside down
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["corner"],
-                PI,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "corner",
+                rotation: PI,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Horizontal,
@@ -182,15 +326,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(drawCorner, 90)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["corner"],
-                PI * 1.5,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "corner",
+                rotation: PI * 1.5,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Vertical,
@@ -205,15 +346,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"rotate(drawCorner, 180)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["corner"],
-                0.0,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "corner",
+                rotation: 0.0,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Vertical,
@@ -228,15 +366,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(drawCorner, 270)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["corner"],
-                PI * 0.5,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "corner",
+                rotation: PI * 0.5,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Horizontal,
@@ -244,15 +379,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             ..
         } => {
             // "drawHorizontalLine"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["vertical_line"],
-                PI / 2.0,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "vertical_line",
+                rotation: PI / 2.0,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Vertical,
@@ -260,15 +392,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             ..
         } => {
             // "drawVerticalLine"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["vertical_line"],
-                0.0,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "vertical_line",
+                rotation: 0.0,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Vertical,
@@ -277,15 +406,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"drawCurvedCross"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross"],
-                PI,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross",
+                rotation: PI,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Vertical,
@@ -294,15 +420,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"drawCurvedCrossUnder"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross_under"],
-                0.0,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross_under",
+                rotation: 0.0,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Vertical,
@@ -311,15 +434,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"flipHorizontally(drawCurvedCrossUnder)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross_under"],
-                0.0,
-                tile_size,
-                true,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross_under",
+                rotation: 0.0,
+                flip_x: true,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Vertical,
@@ -328,15 +448,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"flipHorizontally(drawCurvedCross)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross"],
-                PI,
-                tile_size,
-                true,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross",
+                rotation: PI,
+                flip_x: true,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -345,15 +462,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(drawCurvedCrossUnder, 180)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross_under"],
-                PI,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross_under",
+                rotation: PI,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -362,15 +476,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"rotate(flipHorizontally(drawCurvedCross), 180)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross"],
-                0.0,
-                tile_size,
-                true,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross",
+                rotation: 0.0,
+                flip_x: true,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -379,15 +490,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"rotate(drawCurvedCross, 180)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross"],
-                0.0,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross",
+                rotation: 0.0,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -396,15 +504,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(flipHorizontally(drawCurvedCrossUnder), 180)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross_under"],
-                PI,
-                tile_size,
-                true,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross_under",
+                rotation: PI,
+                flip_x: true,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Horizontal,
@@ -413,15 +518,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(flipHorizontally(drawCurvedCross), 90)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross"],
-                PI*1.5,
-                tile_size,
-                true,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross",
+                rotation: PI*1.5,
+                flip_x: true,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Horizontal,
@@ -430,15 +532,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"rotate(drawCurvedCrossUnder, 270)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross_under"],
-                PI * 1.5,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross_under",
+                rotation: PI * 1.5,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Horizontal,
@@ -447,15 +546,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"rotate(flipHorizontally(drawCurvedCrossUnder), 90)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross_under"],
-                PI / 2.0,
-                tile_size,
-                true,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross_under",
+                rotation: PI / 2.0,
+                flip_x: true,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Horizontal,
@@ -464,15 +560,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(drawCurvedCross, 270)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross"],
-                PI * 0.5,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross",
+                rotation: PI * 0.5,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -481,15 +574,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(flipHorizontally(drawCurvedCrossUnder), 270)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross_under"],
-                PI * 1.5,
-                tile_size,
-                true,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross_under",
+                rotation: PI * 1.5,
+                flip_x: true,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -498,15 +588,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"rotate(drawCurvedCross, 90)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross"],
-                PI * 1.5,
-                tile_size,
-                false,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross",
+                rotation: PI * 1.5,
+                flip_x: false,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -515,15 +602,12 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Even,
         } => {
             //"rotate(flipHorizontally(drawCurvedCross), 270)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross"],
-                PI * 0.5,
-                tile_size,
-                true,
-                false,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross",
+                rotation: PI * 0.5,
+                flip_x: true,
+                flip_y: false,
+            })
         }
         Tile {
             bottom_cut: Cut::Open,
@@ -532,25 +616,13 @@ pub fn draw_expr_for_tile(texture: &Texture2D, tile: Tile, top_left: Vec2, tile_
             col_offset: Offset::Odd,
         } => {
             //"rotate(drawCurvedCrossUnder, 90)"
-            draw_tile(
-                texture,
-                top_left,
-                TILE_LOCS["curved_cross_under"],
-                PI / 2.0,
-                tile_size,
-                false,
-                false,
-            );
-        }
-        _ => {
-            // error
-            draw_rectangle(
-                top_left.x,
-                top_left.y,
-                tile_size.into(),
-                tile_size.into(),
-                BLACK,
-            );
+            Some(TilePlacement {
+                drawing: "curved_cross_under",
+                rotation: PI / 2.0,
+                flip_x: false,
+                flip_y: false,
+            })
         }
+        _ => None,
     }
 }
@@ -0,0 +1,151 @@
+/*
+ * Fixed-timestep clock deciding when the life board should step, independent of frame rate.
+ */
+
+use std::ops::RangeInclusive;
+
+// rates at or below this many generations per second on a slider mean step by hand only
+pub const MANUAL_RATE: f32 = 0.05;
+// generations per second a clock can run at
+pub const RATE_RANGE: RangeInclusive<f32> = 0.1..=30.0;
+// generations per second for quick changes of speed, e.g. from the number keys
+pub const RATE_PRESETS: [f32; 5] = [1.0, 2.0, 5.0, 10.0, 30.0];
+// default for StepClock::max_catch_up_steps
+pub const DEFAULT_MAX_CATCH_UP_STEPS: usize = 8;
+
+pub struct StepClock {
+    // generations per second, within RATE_RANGE
+    rate: f32,
+    // only step by hand, whatever the rate
+    manual: bool,
+    // time the most recent step was due
+    last_step: f64,
+    // most steps a single tick will ask for. Any more that are owed, e.g. after the app has
+    // been in the background, are dropped rather than holding up the frame
+    pub max_catch_up_steps: usize,
+}
+
+impl StepClock {
+    pub fn new(rate: f32, now: f64) -> Self {
+        StepClock {
+            rate: clamp_rate(rate),
+            manual: false,
+            last_step: now,
+            max_catch_up_steps: DEFAULT_MAX_CATCH_UP_STEPS,
+        }
+    }
+
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    pub fn set_rate(&mut self, rate: f32) {
+        /* rates outside RATE_RANGE are brought into it */
+        self.rate = clamp_rate(rate);
+    }
+
+    pub fn is_manual(&self) -> bool {
+        self.manual
+    }
+
+    pub fn set_manual(&mut self, manual: bool) {
+        self.manual = manual;
+    }
+
+    pub fn slider_rate(&self) -> f32 {
+        /* the rate for a slider running from 0, where 0 means manual */
+        if self.manual {
+            0.0
+        } else {
+            self.rate
+        }
+    }
+
+    pub fn set_slider_rate(&mut self, rate: f32) {
+        /* set the rate from a slider running from 0. The bottom of it, up to MANUAL_RATE,
+         * means step by hand */
+        self.set_manual(rate <= MANUAL_RATE);
+        self.set_rate(rate);
+    }
+
+    pub fn use_preset(&mut self, preset: usize) -> Option<f32> {
+        /* run at RATE_PRESETS[preset], even if stepping by hand until now. Returns the new
+         * rate, or None (leaving the clock alone) if there's no such preset */
+        let rate = *RATE_PRESETS.get(preset)?;
+        self.set_manual(false);
+        self.set_rate(rate);
+        Some(rate)
+    }
+
+    fn interval(&self) -> f64 {
+        1.0 / self.rate as f64
+    }
+
+    pub fn tick(&mut self, now: f64) -> usize {
+        /* number of steps due since the last tick, up to max_catch_up_steps.
+         * Never any at a manual rate */
+        if self.is_manual() {
+            self.reset(now);
+            return 0;
+        }
+        let mut n_steps = 0;
+        while now - self.last_step >= self.interval() {
+            if n_steps == self.max_catch_up_steps.max(1) {
+                // drop the rest, keeping how far into the current interval we are
+                self.last_step = now - (now - self.last_step) % self.interval();
+                break;
+            }
+            self.last_step += self.interval();
+            n_steps += 1;
+        }
+        n_steps
+    }
+
+    pub fn reset(&mut self, now: f64) {
+        /* start timing afresh, e.g. after being paused, so no steps are owed */
+        self.last_step = now;
+    }
+
+    pub fn fraction(&self, now: f64) -> f32 {
+        /* how far through the current step interval we are, from 0 to 1 */
+        if self.is_manual() {
+            return 1.0;
+        }
+        (((now - self.last_step) / self.interval()) as f32).clamp(0.0, 1.0)
+    }
+}
+
+fn clamp_rate(rate: f32) -> f32 {
+    if rate.is_nan() {
+        *RATE_RANGE.start()
+    } else {
+        rate.clamp(*RATE_RANGE.start(), *RATE_RANGE.end())
+    }
+}
+
+pub struct Stopwatch {
+    /* measures short stretches of work, e.g. for a profiler overlay. std::time::Instant
+     * isn't available in the browser, so there it falls back on macroquad's clock */
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+    #[cfg(target_arch = "wasm32")]
+    start: f64,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Stopwatch {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+            #[cfg(target_arch = "wasm32")]
+            start: macroquad::time::get_time(),
+        }
+    }
+
+    pub fn elapsed_us(&self) -> u64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.start.elapsed().as_micros() as u64;
+        #[cfg(target_arch = "wasm32")]
+        return ((macroquad::time::get_time() - self.start) * 1_000_000.0) as u64;
+    }
+}
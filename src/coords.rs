@@ -0,0 +1,212 @@
+/*
+ * Conversions between the three grids the app works with:
+ *
+ *  - life cells, a plain width x height grid
+ *  - knot tiles, also a plain grid, usually the same size as the cells
+ *  - knot nodes, which sit on tile corners in a quincunx: even rows of nodes start at the
+ *    left edge and odd rows are shifted right by one tile, so along a row nodes are 2 tiles
+ *    apart. Node (x, y) is at tile corner (2x, y) on even rows and (2x + 1, y) on odd ones.
+ *
+ * Everything here is pure arithmetic so it can be checked without a window.
+ */
+use crate::error::ConbhuideError;
+use macroquad::prelude::*;
+
+pub fn grid_index(x: u16, y: u16, width: u16) -> usize {
+    /* index of x,y in a row-major 1d vector of cells or tiles */
+    y as usize * width as usize + x as usize
+}
+
+pub fn physical_to_logical(screen_pos: Vec2, dpi_scale: f32) -> Vec2 {
+    /* a position in physical pixels as logical pixels, the units the grids are drawn in.
+     * On a high-DPI display there are dpi_scale of one to each of the other */
+    if dpi_scale > 0.0 {
+        screen_pos / dpi_scale
+    } else {
+        screen_pos
+    }
+}
+
+pub fn parse_grid_pos(text: &str) -> Result<(u16, u16), ConbhuideError> {
+    /* a position typed in as x,y, e.g. "10,5". Spaces round either number are fine */
+    let bad = || ConbhuideError::Parse(format!("bad position '{}', expected e.g. 10,5", text));
+    let mut parts = text.split(',').map(str::trim);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(x), Some(y), None) => Ok((x.parse().map_err(|_| bad())?, y.parse().map_err(|_| bad())?)),
+        _ => Err(bad()),
+    }
+}
+
+pub fn screen_to_grid(screen_pos: Vec2, size: u16, offset: Vec2) -> (u16, u16) {
+    /* cell or tile containing a point on the screen */
+    (
+        (screen_pos.x - offset.x) as u16 / size,
+        (screen_pos.y - offset.y) as u16 / size,
+    )
+}
+
+pub fn screen_to_grid_signed(screen_pos: Vec2, size: u16, offset: Vec2) -> (i32, i32) {
+    /* as screen_to_grid, but points above or left of the grid give negative positions */
+    (
+        ((screen_pos.x - offset.x) / size as f32).floor() as i32,
+        ((screen_pos.y - offset.y) / size as f32).floor() as i32,
+    )
+}
+
+pub fn grid_to_screen(x: u16, y: u16, size: u16, offset: Vec2) -> Vec2 {
+    /* top left corner of a cell or tile on the screen */
+    vec2(
+        offset.x + (x as f32 * size as f32),
+        offset.y + (y as f32 * size as f32),
+    )
+}
+
+pub fn cell_to_tile(x: u16, y: u16, cell_size: u16, tile_size: u16) -> (u16, u16) {
+    /* knot tile covering the top left corner of a life cell */
+    (
+        (x as u32 * cell_size as u32 / tile_size as u32) as u16,
+        (y as u32 * cell_size as u32 / tile_size as u32) as u16,
+    )
+}
+
+pub fn tile_to_cell(x: u16, y: u16, tile_size: u16, cell_size: u16) -> (u16, u16) {
+    /* life cell under the middle of a knot tile */
+    let center = |i: u16| ((2 * i as u32 + 1) * tile_size as u32 / (2 * cell_size as u32)) as u16;
+    (center(x), center(y))
+}
+
+pub fn tile_to_nodes(x: u16, y: u16) -> [(u16, u16); 2] {
+    /* the two nodes on opposite corners of a tile. Only one diagonal of each tile
+     * has nodes on it, and which one alternates like a checkerboard */
+    if (x + y) % 2 == 0 {
+        [(x / 2, y), (x.div_ceil(2), y + 1)]
+    } else {
+        [(x.div_ceil(2), y), (x / 2, y + 1)]
+    }
+}
+
+pub fn node_to_screen(x: u16, y: u16, tile_size: u16, offset: Vec2) -> Vec2 {
+    /* return offset position of a node on screen */
+    let corner_x = 2 * x + y % 2;
+    grid_to_screen(corner_x, y, tile_size, offset)
+}
+
+pub fn node_cols_for_row(width: u16, y: u16) -> u16 {
+    /* number of nodes in row y of a knot width tiles across. Even rows have a node on
+     * both the left and right edge of the frame; odd rows are offset by one tile */
+    if y % 2 == 0 {
+        width / 2 + 1
+    } else {
+        width.div_ceil(2)
+    }
+}
+
+pub fn node_count(width: u16, height: u16) -> usize {
+    /* total number of nodes on the height + 1 rows of tile corners */
+    (0..=height)
+        .map(|y| node_cols_for_row(width, y) as usize)
+        .sum()
+}
+
+pub fn node_positions(width: u16, height: u16, tile_size: u16, offset: Vec2) -> Vec<Vec2> {
+    /* node_to_screen for every node, in node_index order */
+    (0..=height)
+        .flat_map(|y| (0..node_cols_for_row(width, y)).map(move |x| node_to_screen(x, y, tile_size, offset)))
+        .collect()
+}
+
+pub fn node_index(x: u16, y: u16, width: u16) -> usize {
+    /* index of node x,y in a 1d vector of nodes, allowing for odd rows being shorter */
+    let n_even_rows = y.div_ceil(2) as usize;
+    let n_odd_rows = (y / 2) as usize;
+    n_even_rows * node_cols_for_row(width, 0) as usize
+        + n_odd_rows * node_cols_for_row(width, 1) as usize
+        + x as usize
+}
+
+/*
+ * Positions on each of the three grids, so a cell can't be passed where a tile or node is
+ * wanted. The tuple forms convert both ways for code that hasn't moved over yet.
+ */
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CellPos {
+    pub x: u16,
+    pub y: u16,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TilePos {
+    pub x: u16,
+    pub y: u16,
+}
+
+// signed, since edges past the grid boundary (e.g. when wrapping) have nodes off it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NodePos {
+    pub x: i16,
+    pub y: i16,
+}
+
+impl CellPos {
+    pub fn to_tile(self, cell_size: u16, tile_size: u16) -> TilePos {
+        cell_to_tile(self.x, self.y, cell_size, tile_size).into()
+    }
+
+    pub fn to_screen(self, cell_size: u16, offset: Vec2) -> Vec2 {
+        grid_to_screen(self.x, self.y, cell_size, offset)
+    }
+}
+
+impl TilePos {
+    pub fn nodes(self) -> [NodePos; 2] {
+        tile_to_nodes(self.x, self.y).map(|(x, y)| NodePos { x: x as i16, y: y as i16 })
+    }
+
+    pub fn to_screen(self, tile_size: u16, offset: Vec2) -> Vec2 {
+        grid_to_screen(self.x, self.y, tile_size, offset)
+    }
+}
+
+impl NodePos {
+    pub fn corner(self) -> (i16, i16) {
+        /* tile corner the node sits on */
+        (2 * self.x + self.y.rem_euclid(2), self.y)
+    }
+}
+
+impl From<(u16, u16)> for CellPos {
+    fn from((x, y): (u16, u16)) -> Self {
+        CellPos { x, y }
+    }
+}
+
+impl From<CellPos> for (u16, u16) {
+    fn from(pos: CellPos) -> Self {
+        (pos.x, pos.y)
+    }
+}
+
+impl From<(u16, u16)> for TilePos {
+    fn from((x, y): (u16, u16)) -> Self {
+        TilePos { x, y }
+    }
+}
+
+impl From<TilePos> for (u16, u16) {
+    fn from(pos: TilePos) -> Self {
+        (pos.x, pos.y)
+    }
+}
+
+impl From<(i16, i16)> for NodePos {
+    fn from((x, y): (i16, i16)) -> Self {
+        NodePos { x, y }
+    }
+}
+
+impl From<NodePos> for (i16, i16) {
+    fn from(pos: NodePos) -> Self {
+        (pos.x, pos.y)
+    }
+}
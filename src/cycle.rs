@@ -0,0 +1,77 @@
+/*
+ * Spotting a board that has settled into a cycle while it runs.
+ *
+ * A CycleWatch remembers the shape of the last few generations it's been shown (the live
+ * cells cropped to their bounding box, and where that box was) and reports a Cycle as soon
+ * as a shape comes round again. The board is never stepped here, so it can watch the
+ * interactive board as well as run headless searches.
+ */
+use crate::life::{CellMatrix, Cycle};
+use std::collections::HashMap;
+use std::fmt;
+
+// live cells cropped to their bounding box, as in Pattern::cells
+type Shape = Vec<(u16, u16)>;
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.period, self.dx, self.dy) {
+            (1, 0, 0) => write!(f, "Still life"),
+            (period, 0, 0) => write!(f, "Oscillator, period {}", period),
+            (period, _, _) => write!(f, "Spaceship, period {}", period),
+        }
+    }
+}
+
+pub struct CycleWatch {
+    // generations remembered, so cycles longer than this go unnoticed
+    window: u64,
+    // each shape seen, with the latest generation it was seen at and where its top left was
+    seen: HashMap<Shape, (u64, (u16, u16))>,
+    // generation and result of the most recent observe
+    last: Option<(u64, Option<Cycle>)>,
+}
+
+impl CycleWatch {
+    pub fn new(window: usize) -> Self {
+        CycleWatch {
+            window: window.max(1) as u64,
+            seen: HashMap::new(),
+            last: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.last = None;
+    }
+
+    pub fn observe(&mut self, board: &CellMatrix) -> Option<Cycle> {
+        /* note the board's current generation and return the cycle it's in, if it repeats one
+         * remembered. Showing the same generation twice just gives the same answer again, and
+         * going back to an earlier one (reseeding, stepping back) starts afresh */
+        let generation = board.generation();
+        match self.last {
+            Some((last_generation, cycle)) if last_generation == generation => return cycle,
+            Some((last_generation, _)) if last_generation > generation => self.clear(),
+            _ => {}
+        }
+        let origin = board.bounding_box().map_or((0, 0), |(x0, y0, _, _)| (x0, y0));
+        let cycle = self
+            .seen
+            .insert(board.live_pattern().cells, (generation, origin))
+            .filter(|&(earlier, _)| generation - earlier <= self.window)
+            .map(|(earlier, earlier_origin)| Cycle {
+                period: (generation - earlier) as usize,
+                dx: origin.0 as i32 - earlier_origin.0 as i32,
+                dy: origin.1 as i32 - earlier_origin.1 as i32,
+            });
+        // forget generations that have dropped out of the window
+        if self.seen.len() as u64 > self.window {
+            let window = self.window;
+            self.seen.retain(|_, &mut (seen_at, _)| generation - seen_at < window);
+        }
+        self.last = Some((generation, cycle));
+        cycle
+    }
+}
@@ -41,37 +41,271 @@
 
   I think it's probably easier to evolve the edges directly.
 */
-use crate::celtic::{draw_expr_for_tile, Cut, Offset, Tile};
+use crate::celtic::{
+    blit_tile, check_tile_locs, default_tile_locs, draw_expr_for_tile, placement_for_tile, Cut, HalfTints, Offset, Tile,
+    TileLocs,
+};
+#[cfg(feature = "embedded-texture")]
+use crate::celtic::embedded_texture;
+use crate::clock::Stopwatch;
+use crate::coords::{
+    grid_to_screen, node_cols_for_row, node_count, node_index, node_positions, node_to_screen, screen_to_grid,
+    tile_to_cell, NodePos, TilePos,
+};
+use crate::error::ConbhuideError;
+use crate::life::{CellMatrix, Dimensions};
+use crate::rule::EdgeRule;
 use macroquad::prelude::*;
-use std::collections::HashSet;
-use std::cmp::max;
+use macroquad::rand::gen_range;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f32::consts::PI;
+
+// widest render target WebGL is sure to allow
+#[cfg(target_arch = "wasm32")]
+const MAX_WASM_RENDER_SIZE: u32 = 4096;
+
+// an edge between two nodes, as stored in TileMatrix::edges
+pub type Edge = ((i16, i16), (i16, i16));
+
+// one edit to the knot, as recorded in TileMatrix::history
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EdgeOp {
+    Add { a: (i16, i16), b: (i16, i16) },
+    Remove { a: (i16, i16), b: (i16, i16) },
+    // every edge swapped at once, e.g. by randomizing, stepping, loading or clearing.
+    // Each edge is listed once, as in to_json
+    Replace { before: Vec<Edge>, after: Vec<Edge> },
+}
+
+fn edge_list(edges: &HashSet<Edge>) -> Vec<Edge> {
+    /* each edge once, in order */
+    let mut list: Vec<Edge> = edges.iter().copied().filter(|(a, b)| a <= b).collect();
+    list.sort_unstable();
+    list
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KnotExtents {
+    /* exactly what a TileMatrix draws: every tile is whole, and there are no partial rows */
+    pub tile_cols: u16,
+    pub tile_rows: u16,
+    // nodes sit on tile corners, so there is one more row of them than of tiles
+    pub node_rows: u16,
+    // even rows have a node at both ends, odd rows are offset by a tile
+    pub even_row_nodes: u16,
+    pub odd_row_nodes: u16,
+    // pixels the tiles cover, from frame_top_left
+    pub size: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EditMode {
+    // clicking an edge adds it if missing and removes it if present
+    Toggle,
+    Add,
+    Erase,
+}
+
+fn cut_where(corner: (i16, i16), has_edge: impl Fn((i16, i16), (i16, i16)) -> bool) -> Cut {
+    /* cut made through a tile corner by whichever edges has_edge says are there */
+    let (corner_x, corner_y) = corner;
+    let node_x = corner_x.div_euclid(2);
+    let vert_exists = has_edge((node_x, corner_y - 1), (node_x, corner_y + 1));
+    let hori_exists = has_edge(
+        ((corner_x - 1).div_euclid(2), corner_y),
+        ((corner_x + 1).div_euclid(2), corner_y),
+    );
+    match (vert_exists, hori_exists) {
+        (true, true) => Cut::Cross,
+        (true, false) => Cut::Vertical,
+        (false, true) => Cut::Horizontal,
+        (false, false) => Cut::Open
+    }
+}
+
+fn swap_over_under(tile: &mut Tile) {
+    /* the same strands, but going under where they went over and over where they went under.
+     * Swapping both offsets turns the drawing half a turn (or picks the other curved cross),
+     * which keeps the strands where they were; swapping just one would mirror them */
+    let swap = |offset: &Offset| match offset {
+        Offset::Even => Offset::Odd,
+        Offset::Odd => Offset::Even,
+    };
+    tile.row_offset = swap(&tile.row_offset);
+    tile.col_offset = swap(&tile.col_offset);
+}
 
 pub struct TileMatrix {
     pub width: u16,
     pub height: u16,
     tile_size: u16,
     nodes: Vec<bool>,
-    pub edges: HashSet<((i16, i16),(i16,i16))>,
+    // where each node is on screen, indexed like nodes. Worked out once, in new
+    node_locs: Vec<Vec2>,
+    pub edges: HashSet<Edge>,
+    // breaks: a second layer of edges, stored the same way, that cut the strands just as edges
+    // do but are left alone by anything that changes the edges (stepping, randomizing,
+    // clearing, undo). For walls that shape a design whatever happens to the rest of it
+    pub breaks: HashSet<Edge>,
     texture: Texture2D,
-    frame_top_left: Vec2
+    // where each drawing is in the texture
+    tile_locs: TileLocs,
+    frame_top_left: Vec2,
+    pub show_nodes: bool,
+    pub show_edge_lines: bool,
+    pub even_node_color: Color,
+    pub odd_node_color: Color,
+    pub edge_color: Color,
+    pub break_color: Color,
+    // for drawing just the strands, without tiles (see vector.rs)
+    pub strand_color: Color,
+    pub strand_background: Color,
+    // if set, each strand is first drawn in this color shifted by outline_offset, as a shadow
+    pub outline_color: Option<Color>,
+    pub outline_offset: Vec2,
+    // if set, tiles are tinted to show the weave: the first color where a strand goes over at a
+    // crossing, the second where it goes under. Multiplies the texture, so WHITE leaves it be
+    pub weave_colors: Option<(Color, Color)>,
+    pub line_thickness: f32,
+    // cap each edge with a circle so joints between edges look smooth
+    pub rounded_joints: bool,
+    // treat the grid as a torus so strands leaving one side re-enter on the other.
+    // needs an even width and height for the node rows to line up at the seams
+    pub wrap: bool,
+    pub edit_mode: EditMode,
+    // clicks edit the breaks instead of the edges
+    pub edit_breaks: bool,
+    // mark tiles whose edges can't be drawn in red, instead of leaving them black
+    pub highlight_invalid: bool,
+    // leave out tiles with no edges at their corners, e.g. for a sparse knot on a transparent
+    // background
+    pub skip_open: bool,
+    // crossings where fix_weaving overrode the usual over/under, keyed by corner: true if the
+    // strand running down and to the right goes over there (see strand.rs)
+    pub weaving: HashMap<(i16, i16), bool>,
+    // (rows, columns): treat odd rows or columns as even and vice versa when picking tile
+    // drawings. Shifting one of them swaps over and under at every crossing
+    pub parity_shift: (bool, bool),
+    // every edit made to the edges, oldest first
+    pub history: Vec<EdgeOp>,
+    // time draw_tiles, for a profiler overlay
+    pub profile: bool,
+    last_draw_us: Cell<u64>,
 }
 impl TileMatrix {
     pub fn new(screen_size: Vec2, tile_size: u16, texture: Texture2D, frame_top_left: Option<Vec2>) -> Self {
-        let width: u16 = (screen_size.x / tile_size as f32) as u16;
-        let height: u16 = (screen_size.y / tile_size as f32) as u16;
-        // to make edges, we should have 1 fewer nodes than columns of tiles,
-        // and 1 more than rows
+        // at least one tile, however small the screen, so width - 1 and the like stay valid
+        let tile_size = tile_size.max(1);
+        let width: u16 = ((screen_size.x / tile_size as f32) as u16).max(1);
+        let height: u16 = ((screen_size.y / tile_size as f32) as u16).max(1);
+        // nodes sit on tile corners, so there is 1 more row of them than rows of tiles.
+        // Even rows start at the left edge and odd rows are offset by a tile,
+        // so the two parities hold different numbers of nodes
+        let frame_top_left = frame_top_left.unwrap_or(vec2(0.,0.));
         TileMatrix {
             width,
             height,
             tile_size,
-            nodes: vec![false; ((width / 2 + 1) * (height + 1)) as usize],
+            nodes: vec![false; node_count(width, height)],
+            node_locs: node_positions(width, height, tile_size, frame_top_left),
             edges: HashSet::new(),
+            breaks: HashSet::new(),
             texture,
-            frame_top_left: frame_top_left.unwrap_or(vec2(0.,0.))
+            tile_locs: default_tile_locs(),
+            frame_top_left,
+            show_nodes: true,
+            show_edge_lines: true,
+            even_node_color: RED,
+            odd_node_color: BLUE,
+            edge_color: WHITE,
+            break_color: ORANGE,
+            strand_color: BLACK,
+            strand_background: WHITE,
+            outline_color: None,
+            weave_colors: None,
+            outline_offset: vec2(1.0, 1.0) * (tile_size / 10).max(1) as f32,
+            line_thickness: (tile_size / 10).saturating_sub(1) as f32,
+            rounded_joints: false,
+            highlight_invalid: false,
+            skip_open: false,
+            weaving: HashMap::new(),
+            parity_shift: (false, false),
+            history: vec![],
+            profile: false,
+            last_draw_us: Cell::new(0),
+            wrap: false,
+            edit_mode: EditMode::Toggle,
+            edit_breaks: false,
+        }
+    }
+
+    pub fn with_dimensions(
+        width: u16,
+        height: u16,
+        tile_size: u16,
+        texture: Texture2D,
+        frame_top_left: Option<Vec2>,
+    ) -> Result<Self, ConbhuideError> {
+        /* a knot exactly width x height tiles, rather than however many fit on the screen.
+         * Unlike new, nothing is rounded or clamped: a size that can't be drawn is an error */
+        if width == 0 || height == 0 || tile_size == 0 {
+            return Err(ConbhuideError::InvalidDimensions(format!(
+                "A knot needs at least one tile of at least one pixel, not {}x{} tiles of {}",
+                width, height, tile_size
+            )));
+        }
+        // corners and nodes are numbered with i16s, and go one past the last tile
+        if width >= i16::MAX as u16 || height >= i16::MAX as u16 {
+            return Err(ConbhuideError::InvalidDimensions(format!(
+                "A knot can be at most {} tiles across, not {}x{}",
+                i16::MAX - 1,
+                width,
+                height
+            )));
+        }
+        let screen_size = vec2(width as f32, height as f32) * tile_size as f32;
+        Ok(Self::new(screen_size, tile_size, texture, frame_top_left))
+    }
+
+    pub fn extents(&self) -> KnotExtents {
+        KnotExtents {
+            tile_cols: self.width,
+            tile_rows: self.height,
+            node_rows: self.height + 1,
+            even_row_nodes: node_cols_for_row(self.width, 0),
+            odd_row_nodes: node_cols_for_row(self.width, 1),
+            size: vec2(self.width as f32, self.height as f32) * self.tile_size as f32,
+        }
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        /* cell_size here is the tile size */
+        Dimensions {
+            width: self.width,
+            height: self.height,
+            cell_size: self.tile_size,
+            offset: self.frame_top_left,
         }
     }
 
+    #[cfg(feature = "embedded-texture")]
+    pub fn new_embedded(screen_size: Vec2, tile_size: u16) -> Self {
+        /* as new, drawing from the atlas built into the binary */
+        Self::new(screen_size, tile_size, embedded_texture(), None)
+    }
+
+    pub fn set_atlas(&mut self, texture: Texture2D, tile_locs: HashMap<&str, (u16, u16)>) -> Result<(), ConbhuideError> {
+        /* draw the tiles from a different texture, given where each drawing is in it (in tiles).
+         * The drawings are the five in celtic.rs' TILE_LOCS, and must all be there */
+        let tile_locs: TileLocs = tile_locs.into_iter().map(|(name, loc)| (name.to_string(), loc)).collect();
+        check_tile_locs(&tile_locs)?;
+        self.texture = texture;
+        self.tile_locs = tile_locs;
+        Ok(())
+    }
+
     pub fn draw_texture(&self) {
         draw_texture(&self.texture, self.frame_top_left.x, self.frame_top_left.y, WHITE);
     }
@@ -82,55 +316,347 @@ impl TileMatrix {
         self.tile_size / 10
     }
 
-    fn ind_for_pos(&self, x: u16, y: u16) -> usize {
-        /* return tile index for a given x,y coordinate
-         * (cells are stored in a 1d vector) */
-        (y * (self.width - 1)) as usize + x as usize
-    }
-
     fn node_ind_for_pos(&self, x: u16, y: u16) -> usize {
         /* return node index for a given x,y coordinate
          * (cells are stored in a 1d vector)
          * recall there are half as many horizontal nodes
-         * as there are tiles
+         * as there are tiles, and odd rows may be one shorter
          */
-        ((y*(self.width/2 + 1)) + x ) as usize
+        debug_assert!(y <= self.height, "node row {} out of range", y);
+        debug_assert!(
+            x < node_cols_for_row(self.width, y),
+            "node column {} out of range for row {}",
+            x,
+            y
+        );
+        let ind = node_index(x, y, self.width);
+        debug_assert!(ind < self.nodes.len());
+        ind
+    }
+
+    fn node_period(&self) -> (i16, i16) {
+        /* number of distinct node columns and rows when wrapping */
+        ((self.width / 2).max(1) as i16, self.height.max(1) as i16)
+    }
+
+    pub fn wrap_node(&self, node: (i16, i16)) -> (i16, i16) {
+        /* node coordinates folded back onto the grid when wrapping, otherwise unchanged */
+        if self.wrap {
+            let (period_x, period_y) = self.node_period();
+            (node.0.rem_euclid(period_x), node.1.rem_euclid(period_y))
+        } else {
+            node
+        }
+    }
+
+    pub fn has_edge(&self, a: impl Into<NodePos>, b: impl Into<NodePos>) -> bool {
+        /* nodes can be given as NodePos or plain (x, y) tuples */
+        self.edges.contains(&(self.wrap_node(a.into().into()), self.wrap_node(b.into().into())))
+    }
+
+    pub fn node_adjacency(&self) -> HashMap<(i16, i16), Vec<(i16, i16)>> {
+        /* each node with an edge, and the nodes its edges lead to, sorted */
+        let mut adjacency: HashMap<(i16, i16), Vec<(i16, i16)>> = HashMap::new();
+        // edges are stored both ways round, so this lists each edge under both its ends
+        for &(a, b) in &self.edges {
+            adjacency.entry(a).or_default().push(b);
+        }
+        for neighbors in adjacency.values_mut() {
+            neighbors.sort_unstable();
+            neighbors.dedup();
+        }
+        adjacency
+    }
+
+    pub fn connected(&self, a: impl Into<NodePos>, b: impl Into<NodePos>) -> bool {
+        /* whether a path of edges leads from node a to node b. A node is connected to itself */
+        let (a, b) = (self.wrap_node(a.into().into()), self.wrap_node(b.into().into()));
+        let adjacency = self.node_adjacency();
+        let mut seen: HashSet<(i16, i16)> = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(a);
+        queue.push_back(a);
+        while let Some(node) = queue.pop_front() {
+            if node == b {
+                return true;
+            }
+            for &next in adjacency.get(&node).into_iter().flatten() {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        false
+    }
+
+    pub fn has_break(&self, a: impl Into<NodePos>, b: impl Into<NodePos>) -> bool {
+        self.breaks.contains(&(self.wrap_node(a.into().into()), self.wrap_node(b.into().into())))
+    }
+
+    pub fn add_break(&mut self, a: impl Into<NodePos>, b: impl Into<NodePos>) {
+        let (a, b) = (self.wrap_node(a.into().into()), self.wrap_node(b.into().into()));
+        self.breaks.insert((a, b));
+        self.breaks.insert((b, a));
+        self.weaving.clear();
+    }
+
+    pub fn remove_break(&mut self, a: impl Into<NodePos>, b: impl Into<NodePos>) {
+        let (a, b) = (self.wrap_node(a.into().into()), self.wrap_node(b.into().into()));
+        self.breaks.remove(&(a, b));
+        self.breaks.remove(&(b, a));
+        self.weaving.clear();
+    }
+
+    pub fn add_edge(&mut self, a: impl Into<NodePos>, b: impl Into<NodePos>) {
+        /* edges are stored in both directions */
+        let (a, b) = (self.wrap_node(a.into().into()), self.wrap_node(b.into().into()));
+        self.edges.insert((a, b));
+        self.edges.insert((b, a));
+        self.history.push(EdgeOp::Add { a, b });
+        // the strands have changed, so any weaving fixes no longer apply
+        self.weaving.clear();
+    }
+
+    pub fn remove_edge(&mut self, a: impl Into<NodePos>, b: impl Into<NodePos>) {
+        let (a, b) = (self.wrap_node(a.into().into()), self.wrap_node(b.into().into()));
+        self.edges.remove(&(a, b));
+        self.edges.remove(&(b, a));
+        self.history.push(EdgeOp::Remove { a, b });
+        self.weaving.clear();
+    }
+
+    fn set_edges(&mut self, edges: &[Edge]) {
+        /* swap every edge for edges, without recording it */
+        self.edges.clear();
+        for &(a, b) in edges {
+            let (a, b) = (self.wrap_node(a), self.wrap_node(b));
+            self.edges.insert((a, b));
+            self.edges.insert((b, a));
+        }
+        self.weaving.clear();
+    }
+
+    pub fn replace_edges(&mut self, edges: &[Edge]) {
+        /* swap every edge for edges, as a single edit that undo takes back in one go */
+        let before = edge_list(&self.edges);
+        self.set_edges(edges);
+        let after = edge_list(&self.edges);
+        self.history.push(EdgeOp::Replace { before, after });
+    }
+
+    pub fn replay(&mut self, ops: &[EdgeOp]) {
+        /* redo a sequence of edits, e.g. a history recorded on another knot */
+        for op in ops {
+            match op {
+                EdgeOp::Add { a, b } => self.add_edge(*a, *b),
+                EdgeOp::Remove { a, b } => self.remove_edge(*a, *b),
+                EdgeOp::Replace { after, .. } => self.replace_edges(after),
+            }
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        /* take back the latest edit in history. Returns false if there's nothing to undo */
+        let op = match self.history.pop() {
+            Some(op) => op,
+            None => return false,
+        };
+        match op {
+            EdgeOp::Add { a, b } => self.remove_edge(a, b),
+            EdgeOp::Remove { a, b } => self.add_edge(a, b),
+            EdgeOp::Replace { before, .. } => {
+                self.set_edges(&before);
+                return true;
+            }
+        }
+        // the edit just made to undo it isn't one to remember
+        self.history.pop();
+        true
+    }
+
+    pub fn clear_edges(&mut self) {
+        /* remove every edge, as one edit that can be undone */
+        self.replace_edges(&[]);
+    }
+
+    pub fn history_json(&self) -> String {
+        serde_json::to_string(&self.history).unwrap()
+    }
+
+    pub fn replay_json(&mut self, json: &str) -> Result<(), ConbhuideError> {
+        /* replay a history saved with history_json */
+        let ops: Vec<EdgeOp> =
+            serde_json::from_str(json).map_err(|e| ConbhuideError::Parse(format!("Bad edit history: {}", e)))?;
+        self.replay(&ops);
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> String {
+        /* the edges as a JSON list of node pairs, each edge once, for saving with from_json */
+        serde_json::to_string(&edge_list(&self.edges)).unwrap()
+    }
+
+    pub fn from_json(&mut self, json: &str) -> Result<(), ConbhuideError> {
+        /* replace the edges with a list saved by to_json. Nothing changes if any are bad */
+        let edges: Vec<((i16, i16), (i16, i16))> =
+            serde_json::from_str(json).map_err(|e| ConbhuideError::Parse(format!("Bad edge list: {}", e)))?;
+        if let Some(edge) = edges.iter().find(|&&(a, b)| !self.edge_fits(a, b)) {
+            return Err(ConbhuideError::OutOfBounds(format!("Bad edge list: {:?} is off the grid", edge)));
+        }
+        self.replace_edges(&edges);
+        Ok(())
+    }
+
+    fn node_exists(&self, node: (i16, i16)) -> bool {
+        node.0 >= 0 && node.1 >= 0 && self.checked_node_ind_for_pos(node.0 as u16, node.1 as u16).is_some()
+    }
+
+    fn edge_fits(&self, a: (i16, i16), b: (i16, i16)) -> bool {
+        /* whether both ends of an edge are on the grid (always true when wrapping) */
+        self.wrap || (self.node_exists(a) && self.node_exists(b))
+    }
+
+    pub fn randomize_edges(&mut self, density: f32) {
+        /* replace the edges with a random set. Each crossing corner gets a horizontal or
+         * vertical edge through it with probability density, and never both, so every
+         * tile stays drawable */
+        let mut edges = vec![];
+        let (max_x, max_y) = if self.wrap {
+            (self.width as i16 - 1, self.height as i16 - 1)
+        } else {
+            (self.width as i16, self.height as i16)
+        };
+        for corner_y in 0..=max_y {
+            for corner_x in 0..=max_x {
+                if (corner_x + corner_y) % 2 == 0 || gen_range(0.0, 1.0) >= density {
+                    continue;
+                }
+                let horizontal = (
+                    ((corner_x - 1).div_euclid(2), corner_y),
+                    ((corner_x + 1).div_euclid(2), corner_y),
+                );
+                let vertical = (
+                    (corner_x.div_euclid(2), corner_y - 1),
+                    (corner_x.div_euclid(2), corner_y + 1),
+                );
+                let (a, b) = if gen_range(0, 2) == 0 { horizontal } else { vertical };
+                if self.edge_fits(a, b) {
+                    edges.push((a, b));
+                }
+            }
+        }
+        self.replace_edges(&edges);
+    }
+
+    pub fn step_edges(&mut self, rule: &EdgeRule) {
+        /* evolve the edges one step by rule, counting each edge's neighbors over the node
+         * adjacency graph, then snap the result back onto edges the tiles can draw.
+         *
+         * Each crossing corner can hold a horizontal edge, a vertical one, or neither, but not
+         * both, since two edges through one corner cut every strand there. Applying the rule
+         * to each edge on its own can give both, and the nearest drawable set then differs by
+         * just dropping one of the two. Which one is settled by, in turn: keeping whichever
+         * was there before the step, keeping whichever has more neighbors, and otherwise
+         * keeping the horizontal one. So the same edges always step to the same result */
+        let degree: HashMap<(i16, i16), usize> = self
+            .node_adjacency()
+            .into_iter()
+            .map(|(node, neighbors)| (node, neighbors.len()))
+            .collect();
+        let (max_x, max_y) = if self.wrap {
+            (self.width as i16 - 1, self.height as i16 - 1)
+        } else {
+            (self.width as i16, self.height as i16)
+        };
+        let mut next_edges = vec![];
+        for corner_y in 0..=max_y {
+            for corner_x in 0..=max_x {
+                if (corner_x + corner_y) % 2 == 0 {
+                    continue;
+                }
+                let horizontal = (
+                    ((corner_x - 1).div_euclid(2), corner_y),
+                    ((corner_x + 1).div_euclid(2), corner_y),
+                );
+                let vertical = (
+                    (corner_x.div_euclid(2), corner_y - 1),
+                    (corner_x.div_euclid(2), corner_y + 1),
+                );
+                // (edge, whether it's there now, its neighbor count, whether it's there next)
+                let candidates: Vec<_> = [horizontal, vertical]
+                    .iter()
+                    .copied()
+                    .filter(|&(a, b)| self.edge_fits(a, b))
+                    .map(|(a, b)| {
+                        let present = self.has_edge(a, b);
+                        let degree_of = |node| *degree.get(&self.wrap_node(node)).unwrap_or(&0);
+                        let ends = degree_of(a) + degree_of(b);
+                        // an edge isn't its own neighbor
+                        let n_neighbors = if present { ends - 2 } else { ends };
+                        ((a, b), present, n_neighbors, rule.next_state(present, n_neighbors))
+                    })
+                    .collect();
+                // max_by_key keeps the last of equals, so list the horizontal edge last to win ties
+                if let Some(&(edge, ..)) = candidates
+                    .iter()
+                    .rev()
+                    .filter(|&&(.., next)| next)
+                    .max_by_key(|&&(_, present, n_neighbors, _)| (present, n_neighbors))
+                {
+                    next_edges.push(edge);
+                }
+            }
+        }
+        self.replace_edges(&next_edges);
+    }
+
+    pub fn checked_node_ind_for_pos(&self, x: u16, y: u16) -> Option<usize> {
+        /* node index for x,y, or None if there is no such node */
+        let (x, y) = if self.wrap {
+            let (x, y) = self.wrap_node((x as i16, y as i16));
+            (x as u16, y as u16)
+        } else {
+            (x, y)
+        };
+        if y > self.height || x >= node_cols_for_row(self.width, y) {
+            None
+        } else {
+            Some(self.node_ind_for_pos(x, y))
+        }
+    }
+
+    pub fn node_is_set(&self, x: u16, y: u16) -> bool {
+        /* node state, treating nodes outside the grid as unset */
+        self.checked_node_ind_for_pos(x, y)
+            .is_some_and(|ind| self.nodes[ind])
     }
 
     pub fn loc_for_node(&self, x: u16, y: u16) -> Vec2 {
         /* return offset position of a node on screen */
-        if y % 2 == 0 {
-            vec2(
-                self.frame_top_left.x + ((x * self.tile_size*2) as f32),
-                self.frame_top_left.y + ((y * self.tile_size) as f32),
-            )
+        if y <= self.height && x < node_cols_for_row(self.width, y) {
+            self.node_locs[node_index(x, y, self.width)]
         } else {
-            vec2(
-                self.frame_top_left.x + ((self.tile_size  + x * self.tile_size*2) as f32),
-                self.frame_top_left.y + ((y * self.tile_size) as f32),
-            )
+            // off the grid, so not worked out in advance
+            node_to_screen(x, y, self.tile_size, self.frame_top_left)
         }
     }
 
     pub fn loc_for_tile(&self, x: u16, y: u16) -> Vec2 {
         /* return position of a tile on screen */
-            vec2(
-                self.frame_top_left.x + ((x * self.tile_size) as f32),
-                self.frame_top_left.y + ((y * self.tile_size) as f32)
-            )
+        grid_to_screen(x, y, self.tile_size, self.frame_top_left)
     }
 
     pub fn tile_pos_for_click(&self, screen_pos: Vec2) -> (u16, u16) {
         /* translate a click on the screen to a tile position */
-        info!("Screen position {},{}", screen_pos.x, screen_pos.y,);
-        (
-            (screen_pos.x - self.frame_top_left.x) as u16 / self.tile_size,
-            (screen_pos.y - self.frame_top_left.y) as u16 / self.tile_size,
-        )
+        screen_to_grid(screen_pos, self.tile_size, self.frame_top_left)
     }
 
     fn nearest_edge_to_click(&self, screen_pos: Vec2) -> ((u16, u16), (u16, u16)) {
-        /* nearest pair of nodes (constituting an edge) to click */
+        /* nearest pair of nodes (constituting an edge) to click. The first node is the one
+         * nearest the click, and the edge is whichever of the (up to) four from it has its
+         * midpoint closest to the click. Where midpoints are exactly as close, as at the node
+         * itself, horizontal edges win over vertical ones, then right over left and down
+         * over up */
         let y_ft: f32 = (screen_pos.y - self.frame_top_left.y) / self.tile_size as f32;
         let y_1: u16 = y_ft.round() as u16;
         let x_ft: f32 = if y_1 % 2 == 0 {
@@ -140,17 +666,20 @@ impl TileMatrix {
         };
         let x_1: u16 = x_ft.round() as u16;
 
-        let x_dist = x_ft - x_1 as f32;
-        let y_dist = y_ft - y_1 as f32;
-
-        let (x_2, y_2) = if x_dist >= y_dist {
-            (max(0,(x_1 as i16) + if x_dist >= 0.0 {1} else {-1}) as u16,
-            y_1)
-        } else {
-            (x_1,
-            max(0,(y_1 as i16) + if y_dist >= 0.0 {2} else {-2}) as u16)
-        };
-        // So that's the nearest node. The second node will be whichever one
+        // neighboring nodes, in order of preference when it's a tie
+        let node_loc = |x: u16, y: u16| node_to_screen(x, y, self.tile_size, self.frame_top_left);
+        let nearest_loc = node_loc(x_1, y_1);
+        let (x_2, y_2) = [(1, 0), (-1, 0), (0, 2), (0, -2)]
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let (x, y) = (x_1 as i16 + dx, y_1 as i16 + dy);
+                (x >= 0 && y >= 0).then_some((x as u16, y as u16))
+            })
+            .map(|(x, y)| ((x, y), screen_pos.distance_squared((nearest_loc + node_loc(x, y)) / 2.0)))
+            // min_by keeps the first of equals
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(node, _)| node)
+            .unwrap_or((x_1 + 1, y_1));
         info!("Nearest node to click {},{} is {}, {}", screen_pos.x, screen_pos.y, x_1, y_1);
         ((x_1, y_1), (x_2,y_2))
     }
@@ -159,134 +688,391 @@ impl TileMatrix {
         // TODO: need a data structure that's indifferent to the order in which the edges are
         // stored
         let node_pair_u = self.nearest_edge_to_click(mouse_position);
-        let node_pair = ((node_pair_u.0.0 as i16, node_pair_u.0.1 as i16), (node_pair_u.1.0 as i16, node_pair_u.1.1 as i16));
-        let mut node_pair_rev = node_pair.clone();
-        node_pair_rev = ((node_pair_rev.1.0, node_pair_rev.1.1), (node_pair_rev.0.0, node_pair_rev.0.1));
+        let node_pair = (
+            self.wrap_node((node_pair_u.0.0 as i16, node_pair_u.0.1 as i16)),
+            self.wrap_node((node_pair_u.1.0 as i16, node_pair_u.1.1 as i16)),
+        );
+        if !self.edge_fits(node_pair.0, node_pair.1) {
+            // e.g. past the right edge, where odd rows have one node fewer than even rows
+            info!("No edge at {:?}", node_pair);
+            return;
+        }
 
-        let add_rem: &str;
-        if self.edges.contains(&node_pair) {
-            add_rem = "Removed";
-            self.edges.remove(&node_pair);
-            self.edges.remove(&node_pair_rev);
+        let (layer, present) = if self.edit_breaks {
+            ("break", self.breaks.contains(&node_pair))
         } else {
-            add_rem = "Added";
-            self.edges.insert(node_pair);
-            self.edges.insert(node_pair_rev);
+            ("edge", self.edges.contains(&node_pair))
+        };
+        let add_rem: &str;
+        match (self.edit_mode, present) {
+            (EditMode::Toggle, true) | (EditMode::Erase, true) => {
+                add_rem = "Removed";
+                if self.edit_breaks {
+                    self.remove_break(node_pair.0, node_pair.1);
+                } else {
+                    self.remove_edge(node_pair.0, node_pair.1);
+                }
+            }
+            (EditMode::Toggle, false) | (EditMode::Add, false) => {
+                add_rem = "Added";
+                if self.edit_breaks {
+                    self.add_break(node_pair.0, node_pair.1);
+                } else {
+                    self.add_edge(node_pair.0, node_pair.1);
+                }
+            }
+            (EditMode::Add, true) | (EditMode::Erase, false) => {
+                add_rem = "Left";
+            }
         }
-        info!("{} edge at {:?}", add_rem, node_pair);
+        info!("{} {} at {:?}", add_rem, layer, node_pair);
     }
 
     pub fn draw_tiles(&self) {
-        // draw all the tiles
+        self.draw_tiles_with_life(None);
+    }
+
+    pub fn draw_tiles_with_life(&self, life: Option<&CellMatrix>) {
+        // draw all the tiles, woven by the life board if there is one (see tile_for_pos_with_life)
+        let stopwatch = self.profile.then(Stopwatch::start);
         for x in 0..self.width {
             for y in 0..self.height {
-                let tile: Tile = self.tile_for_pos(x, y);
+                let tile: Tile = self.tile_for_pos_with_life(x, y, life);
+                if self.skips_tile(&tile) {
+                    continue;
+                }
                 let top_left: Vec2 = self.loc_for_tile(x, y);
-                draw_expr_for_tile(&self.texture, tile, top_left, self.tile_size);
+                if self.highlight_invalid && placement_for_tile(&tile).is_none() {
+                    let size = self.tile_size as f32;
+                    draw_rectangle(top_left.x, top_left.y, size, size, RED);
+                } else {
+                    let tints = self.weave_tints(x, y);
+                    draw_expr_for_tile(&self.texture, &self.tile_locs, tile, top_left, self.tile_size, tints);
+                }
             }
         }
+        if let Some(stopwatch) = stopwatch {
+            self.last_draw_us.set(stopwatch.elapsed_us());
+        }
+    }
+
+    fn skips_tile(&self, tile: &Tile) -> bool {
+        self.skip_open && matches!((&tile.top_cut, &tile.bottom_cut), (Cut::Open, Cut::Open))
+    }
+
+    pub fn drawn_tiles(&self, life: Option<&CellMatrix>) -> Vec<(u16, u16)> {
+        /* the tiles draw_tiles_with_life draws, i.e. all of them unless skip_open is set */
+        let mut drawn = vec![];
+        for x in 0..self.width {
+            for y in 0..self.height {
+                if !self.skips_tile(&self.tile_for_pos_with_life(x, y, life)) {
+                    drawn.push((x, y));
+                }
+            }
+        }
+        drawn
+    }
+
+    pub fn last_draw_us(&self) -> u64 {
+        /* microseconds the latest draw_tiles took, while profiling */
+        self.last_draw_us.get()
+    }
+
+    pub fn invalid_tiles(&self) -> Vec<(u16, u16)> {
+        /* tiles whose surrounding edges make a shape no tile drawing can represent */
+        let mut invalid = vec![];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if placement_for_tile(&self.tile_for_pos(x, y)).is_none() {
+                    invalid.push((x, y));
+                }
+            }
+        }
+        invalid
+    }
+
+    pub fn tile_histogram(&self) -> HashMap<String, usize> {
+        /* how many tiles are drawn with each of the five drawings (see placement_for_tile),
+         * whatever their rotation. Tiles that can't be drawn are counted under "error" */
+        let mut histogram = HashMap::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let drawing = placement_for_tile(&self.tile_for_pos(x, y)).map_or("error", |placement| placement.drawing);
+                *histogram.entry(drawing.to_string()).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
+    pub fn tile_info(&self, x: u16, y: u16) -> String {
+        /* what tile_for_pos makes of the tile at x,y and how it's drawn, one fact per line */
+        let tile = self.tile_for_pos(x, y);
+        let drawing = match placement_for_tile(&tile) {
+            Some(placement) => format!(
+                "{}, turned {:.0} degrees{}{}",
+                placement.drawing,
+                placement.rotation.to_degrees(),
+                if placement.flip_x { ", flipped across" } else { "" },
+                if placement.flip_y { ", flipped down" } else { "" }
+            ),
+            None => "none, can't be drawn".to_string(),
+        };
+        format!(
+            "tile {},{}\ntop cut: {:?}\nbottom cut: {:?}\noffsets: row {:?}, column {:?}\ndrawing: {}",
+            x, y, tile.top_cut, tile.bottom_cut, tile.row_offset, tile.col_offset, drawing
+        )
+    }
+
+    pub fn render_tileable(&self, repeats_x: u16, repeats_y: u16) -> (Vec<u8>, u32, u32) {
+        /* RGBA pixels of the knot repeated repeats_x by repeats_y times, e.g. for a wallpaper.
+         * The copies only join up seamlessly when wrap is on, since otherwise strands
+         * stop at the grid boundary. Needs a graphics context to read the atlas back */
+        let atlas = self.texture.get_texture_data();
+        let size = self.tile_size as u32;
+        let knot_width = self.width as u32 * size;
+        let knot_height = self.height as u32 * size;
+        let mut knot = Image::gen_image_color(knot_width as u16, knot_height as u16, BLACK);
+        for x in 0..self.width {
+            for y in 0..self.height {
+                let placement = placement_for_tile(&self.tile_for_pos(x, y));
+                blit_tile(&atlas, &self.tile_locs, placement, self.tile_size, &mut knot, (x as u32 * size, y as u32 * size));
+            }
+        }
+        let width = knot_width * repeats_x as u32;
+        let height = knot_height * repeats_y as u32;
+        let row_bytes = (knot_width as usize * 4).max(1);
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+        for _ in 0..repeats_y {
+            for row in knot.bytes.chunks(row_bytes) {
+                for _ in 0..repeats_x {
+                    pixels.extend_from_slice(row);
+                }
+            }
+        }
+        (pixels, width, height)
+    }
+
+    pub fn render_supersampled(&self, scale: u16) -> (Vec<u8>, u32, u32) {
+        /* RGBA pixels of the knot drawn at scale times its usual size, e.g. for printing.
+         * Unlike render_tileable this draws through an offscreen render target, so the tile
+         * drawings are resampled by the GPU rather than copied pixel for pixel. Needs a graphics
+         * context, and leaves the default camera set, so set your own again afterwards.
+         * WebGL only promises textures MAX_WASM_RENDER_SIZE across, so on wasm the scale is cut
+         * down to fit and the image can come out smaller than asked */
+        let base_width = self.width as u32 * self.tile_size as u32;
+        let base_height = self.height as u32 * self.tile_size as u32;
+        #[cfg(target_arch = "wasm32")]
+        let scale = scale.min((MAX_WASM_RENDER_SIZE / base_width.max(base_height).max(1)) as u16);
+        let scale = scale.max(1) as u32;
+        let (width, height) = (base_width * scale, base_height * scale);
+        let target = render_target(width, height);
+        target.texture.set_filter(FilterMode::Linear);
+        let mut camera = Camera2D::from_display_rect(Rect::new(
+            self.frame_top_left.x,
+            self.frame_top_left.y,
+            base_width as f32,
+            base_height as f32,
+        ));
+        // render targets are read back bottom row first, so draw them upside down
+        // to get the rows out top first
+        camera.zoom.y = -camera.zoom.y;
+        camera.render_target = Some(target.clone());
+        set_camera(&camera);
+        clear_background(BLACK);
+        self.draw_tiles();
+        // draw what's been batched up so far before reading it back
+        unsafe { get_internal_gl() }.flush();
+        set_default_camera();
+        (target.texture.get_texture_data().bytes, width, height)
+    }
+
+    pub fn to_ascii(&self) -> String {
+        /* the knot as text, one character per tile, for eyeballing in a terminal:
+         * ╳ for a straight crossing, │ and ─ for lines, and / or \ for the curved tiles,
+         * following the diagonal the tile's strand runs along. ? marks tiles that can't be drawn */
+        let mut ascii = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let diagonal = if (x + y) % 2 == 0 { '/' } else { '\\' };
+                ascii.push(match placement_for_tile(&self.tile_for_pos(x, y)) {
+                    None => '?',
+                    Some(placement) => match placement.drawing {
+                        "straight_cross" => '╳',
+                        "vertical_line" if (placement.rotation / (PI / 2.0)).round() as i32 % 2 == 0 => '│',
+                        "vertical_line" => '─',
+                        _ => diagonal,
+                    },
+                });
+            }
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    pub fn tile_at(&self, pos: TilePos) -> Tile {
+        self.tile_for_pos(pos.x, pos.y)
     }
 
     pub fn tile_for_pos(&self, x: u16, y: u16) -> Tile {
-        /* instantiate a tile based on information about nearby edges */
+        /* instantiate a tile based on information about nearby edges, with its strands over and
+         * under as the weaving says (see fix_weaving) */
+        let mut tile = self.tile_for_cuts(x, y, self.cut_for_tile(x, y, true), self.cut_for_tile(x, y, false));
+        if self.crossing_swapped(x, y) == Some(true) {
+            swap_over_under(&mut tile);
+        }
+        tile
+    }
+
+    pub fn tile_for_pos_with(&self, x: u16, y: u16, extra_edges: &HashSet<Edge>, removed: &HashSet<Edge>) -> Tile {
+        /* the tile at x,y as it would be with extra_edges added and then removed taken away,
+         * e.g. to preview an edit before making it. Edges can be given either way round */
+        let listed = |edges: &HashSet<Edge>, a: (i16, i16), b: (i16, i16)| {
+            edges
+                .iter()
+                .map(|&(p, q)| (self.wrap_node(p), self.wrap_node(q)))
+                .any(|edge| edge == (a, b) || edge == (b, a))
+        };
+        let has_edge = |a: (i16, i16), b: (i16, i16)| {
+            let (a, b) = (self.wrap_node(a), self.wrap_node(b));
+            (self.edges.contains(&(a, b)) || listed(extra_edges, a, b)) && !listed(removed, a, b)
+                || self.breaks.contains(&(a, b))
+        };
+        let [top, bottom] = Self::crossing_corners(x, y);
+        self.tile_for_cuts(x, y, cut_where(bottom, has_edge), cut_where(top, has_edge))
+    }
+
+    fn tile_for_cuts(&self, x: u16, y: u16, bottom_cut: Cut, top_cut: Cut) -> Tile {
         // note these are odd and even as if things were 1-indexed
-        let row_offset: Offset = if y % 2 == 1 { Offset::Odd } else { Offset::Even };
-        let col_offset: Offset = if x % 2 == 1 { Offset::Odd } else { Offset::Even };
+        let row_offset: Offset = if (y % 2 == 1) != self.parity_shift.0 { Offset::Odd } else { Offset::Even };
+        let col_offset: Offset = if (x % 2 == 1) != self.parity_shift.1 { Offset::Odd } else { Offset::Even };
         Tile {
-            bottom_cut: self.cut_for_tile(x, y, &row_offset, &col_offset, true),
-            top_cut: self.cut_for_tile(x, y, &row_offset, &col_offset, false),
+            bottom_cut,
+            top_cut,
             row_offset,
             col_offset,
         }
     }
 
-    fn cut_for_tile(&self, x: u16, y: u16, row_offset: &Offset, col_offset: &Offset, is_bottom: bool) -> Cut {
-        /* get bottom-most cut on a tile */
-        let n_x: i16 = (x as f32 / 2.0).round() as i16;
-        let n_y: i16 = y as i16;
-        let (vert_exists, hori_exists) = match (is_bottom, row_offset, col_offset) {
-            (true, Offset::Even, Offset::Even) => {
-                (
-                    self.edges.contains(&((n_x,n_y),(n_x,n_y+2))),
-                    self.edges.contains(&((n_x-1,n_y+1),(n_x,n_y+1)))
-                )
-            },
-            (true, Offset::Odd, Offset::Even) => {
-                (
-                    self.edges.contains(&((n_x,n_y),(n_x,n_y+2))),
-                    self.edges.contains(&((n_x,n_y+1),(n_x+1,n_y+1))),
-                )
-            },
-            (true, Offset::Odd, Offset::Odd) => {
-                (
-                    self.edges.contains(&((n_x-1,n_y),(n_x-1,n_y+2))),
-                    self.edges.contains(&((n_x-1,n_y+1),(n_x,n_y+1)))
-                )
-            },
-            (true, Offset::Even, Offset::Odd) => {
-                (
-                    self.edges.contains(&((n_x,n_y),(n_x,n_y+2))),
-                    self.edges.contains(&((n_x-1,n_y+1),(n_x,n_y+1)))
-                )
-            },
-            (false, Offset::Even, Offset::Even) => {
-                (
-                    self.edges.contains(&((n_x,n_y-1), (n_x,n_y+1))),
-                    self.edges.contains(&((n_x,n_y),(n_x+1,n_y)))
-                )
-            },
-            (false, Offset::Odd, Offset::Odd) => {
-                (
-                    self.edges.contains(&((n_x,n_y-1),(n_x,n_y+1))),
-                    self.edges.contains(&((n_x-1,n_y),(n_x,n_y)))
-                )
-            },
-            (false, Offset::Even, Offset::Odd) => {
-                (
-                    self.edges.contains(&((n_x-1,n_y-1), (n_x-1,n_y+1))),
-                    self.edges.contains(&((n_x-1,n_y), (n_x,n_y)))
-                )
-            }
-            (false, Offset::Odd, Offset::Even) => {
-                (
-                    self.edges.contains(&((n_x,n_y-1), (n_x,n_y+1))),
-                    self.edges.contains(&((n_x-1,n_y), (n_x,n_y)))
-                )
-            }
+    pub fn tile_for_pos_with_life(&self, x: u16, y: u16, life: Option<&CellMatrix>) -> Tile {
+        /* as tile_for_pos, but where the life cell under the tile is alive the strands swap
+         * over and under, so the weave shimmers as the board evolves. Only tiles with a
+         * crossing change; corners and lines have nothing to swap */
+        let mut tile = self.tile_for_pos(x, y);
+        let life = match life {
+            Some(life) => life,
+            None => return tile,
         };
-        match (vert_exists, hori_exists) {
-            (true, true) => Cut::Cross,
-            (true, false) => Cut::Vertical,
-            (false, true) => Cut::Horizontal,
-            (false, false) => Cut::Open
+        let (cell_x, cell_y) = tile_to_cell(x, y, self.tile_size, life.dimensions().cell_size);
+        let alive = cell_x < life.width && cell_y < life.height && life.is_alive((cell_x, cell_y));
+        if alive && self.crossing_swapped(x, y).is_some() {
+            swap_over_under(&mut tile);
+        }
+        tile
+    }
+
+    fn cut_for_tile(&self, x: u16, y: u16, is_bottom: bool) -> Cut {
+        /* get bottom-most (or top-most) cut on a tile */
+        let [top, bottom] = Self::crossing_corners(x, y);
+        let corner = if is_bottom { bottom } else { top };
+        self.cut_at_corner(corner.0, corner.1)
+    }
+
+    pub fn cut_at_corner(&self, corner_x: i16, corner_y: i16) -> Cut {
+        /* cut made by any edges or breaks through a tile corner. Only corners between nodes
+         * (where corner_x + corner_y is odd) can have edges through them; these are
+         * where strands cross, and each tile has one at its top and one at its bottom */
+        cut_where((corner_x, corner_y), |a, b| self.has_edge(a, b) || self.has_break(a, b))
+    }
+
+    pub fn crossing_corners(x: u16, y: u16) -> [(i16, i16); 2] {
+        /* (top, bottom) corners of tile x,y where strands cross */
+        let (x, y) = (x as i16, y as i16);
+        if (x + y) % 2 == 0 {
+            [(x + 1, y), (x, y + 1)]
+        } else {
+            [(x, y), (x + 1, y + 1)]
         }
     }
 
+    pub fn weave_tints(&self, x: u16, y: u16) -> Option<HalfTints> {
+        /* how to tint tile x,y with weave_colors set: each half of its strand in the over color
+         * if it goes over at that half's crossing corner (or doesn't cross anything there), in
+         * the under color if it goes under */
+        let (over, under) = self.weave_colors?;
+        let [top, bottom] = Self::crossing_corners(x, y);
+        // the strand runs down and to the right when its top corner is its top left
+        let down_right = top.0 == x as i16;
+        let tint = |corner: (i16, i16)| match self.over_at(corner) {
+            Some(down_right_over) if down_right_over != down_right => under,
+            _ => over,
+        };
+        Some(HalfTints {
+            top: tint(top),
+            bottom: tint(bottom),
+            top_right: !down_right,
+        })
+    }
+
     pub fn draw_edges(&self) {
         /*
          * draw dots for even and odd rows,
          * add edges
          */
-        for y in 0..self.height {
-            let node_color: Color = if y % 2 == 0 { RED } else { BLUE };
-            for x in 0..(self.width / 2 + 1) {
+        if self.show_nodes {
+            self.draw_nodes();
+        }
+        if self.show_edge_lines {
+            self.draw_edge_lines();
+        }
+    }
+
+    pub fn draw_nodes(&self) {
+        for y in 0..=self.height {
+            let node_color: Color = if y % 2 == 0 { self.even_node_color } else { self.odd_node_color };
+            for x in 0..node_cols_for_row(self.width, y) {
                 let node_loc: Vec2 = self.loc_for_node(x, y);
                 draw_circle(node_loc.x, node_loc.y, self.spacing().into(), node_color);
             }
         }
+    }
+
+    pub fn draw_edge_lines(&self) {
+        self.draw_lines_for(&self.edges, self.edge_color);
+        self.draw_lines_for(&self.breaks, self.break_color);
+    }
 
-        //edges
-        for edge in self.edges.iter() {
+    fn draw_lines_for(&self, edges: &HashSet<Edge>, color: Color) {
+        for edge in edges.iter() {
             let node_loc = self.loc_for_node(edge.0.0 as u16, edge.0.1 as u16);
-            let node_loc_end = self.loc_for_node(edge.1.0 as u16, edge.1.1 as u16);
+            let mut node_loc_end = self.loc_for_node(edge.1.0 as u16, edge.1.1 as u16);
+            if self.wrap {
+                // an edge across the seam is stored between nodes on opposite sides of the grid.
+                // draw it heading off screen instead; its reverse draws the other half
+                let (period_x, period_y) = self.node_period();
+                let size = self.tile_size as f32;
+                match edge.1.0 - edge.0.0 {
+                    d if d > 1 => node_loc_end.x -= period_x as f32 * 2.0 * size,
+                    d if d < -1 => node_loc_end.x += period_x as f32 * 2.0 * size,
+                    _ => {}
+                }
+                match edge.1.1 - edge.0.1 {
+                    d if d > 2 => node_loc_end.y -= period_y as f32 * size,
+                    d if d < -2 => node_loc_end.y += period_y as f32 * size,
+                    _ => {}
+                }
+            }
             draw_line(
                 node_loc.x,
                 node_loc.y,
                 node_loc_end.x,
                 node_loc_end.y,
-                (self.spacing() - 1).into(),
-                WHITE,
+                self.line_thickness,
+                color,
             );
+            if self.rounded_joints {
+                draw_circle(node_loc.x, node_loc.y, self.line_thickness / 2.0, color);
+                draw_circle(node_loc_end.x, node_loc_end.y, self.line_thickness / 2.0, color);
+            }
         }
     }
 
@@ -0,0 +1,30 @@
+/*
+ * The one error type for everything in the library that can fail.
+ */
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConbhuideError {
+    // text that couldn't be read: RLE, rules, saved edges or edit histories
+    Parse(String),
+    // a position that isn't on the grid
+    OutOfBounds(String),
+    // a texture that's missing, or missing some of the drawings
+    AssetLoad(String),
+    // a grid that can't be that size
+    InvalidDimensions(String),
+}
+
+impl fmt::Display for ConbhuideError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConbhuideError::Parse(message)
+            | ConbhuideError::OutOfBounds(message)
+            | ConbhuideError::AssetLoad(message)
+            | ConbhuideError::InvalidDimensions(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for ConbhuideError {}
@@ -0,0 +1,90 @@
+/*
+ * Evolving a pattern without opening a window, for batch experiments:
+ *
+ *   conbhuide --headless --input a.rle --gens 1000
+ *
+ * reads a.rle, runs it for 1000 generations and prints the result as RLE.
+ */
+use crate::error::ConbhuideError;
+use crate::life::{BoundaryMode, CellMatrix, CellMatrixBuilder};
+use crate::rle::{decode, decode_rule, encode_pattern, Pattern};
+use crate::rule::Rule;
+use macroquad::prelude::*;
+
+// empty cells left around the live ones on each side whenever the board is (re)built
+const MARGIN: u16 = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadlessArgs {
+    // path to the starting RLE
+    pub input: String,
+    pub generations: u64,
+}
+
+pub fn parse_args(args: &[String]) -> Result<HeadlessArgs, ConbhuideError> {
+    /* read `--input <path> --gens <n>` from the command line, without the program name.
+     * --headless itself is allowed anywhere and skipped */
+    let mut input = None;
+    let mut generations = None;
+    let mut args = args.iter().filter(|arg| *arg != "--headless");
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| ConbhuideError::Parse(format!("{} needs a value", arg)))?;
+        match arg.as_str() {
+            "--input" => input = Some(value.clone()),
+            "--gens" => {
+                generations = Some(
+                    value
+                        .parse()
+                        .map_err(|_| ConbhuideError::Parse(format!("bad generation count '{}'", value)))?,
+                )
+            }
+            _ => return Err(ConbhuideError::Parse(format!("unknown option {}", arg))),
+        }
+    }
+    match (input, generations) {
+        (Some(input), Some(generations)) => Ok(HeadlessArgs { input, generations }),
+        _ => Err(ConbhuideError::Parse("usage: --headless --input <file.rle> --gens <n>".to_string())),
+    }
+}
+
+pub fn evolve_rle(rle: &str, generations: u64) -> Result<String, ConbhuideError> {
+    /* run an RLE pattern for a number of generations, under the rule in its header, and give
+     * back the result as RLE cropped to its live cells. The board is rebuilt bigger whenever
+     * the live cells reach its edge, so they behave as on an unbounded plane (until the board
+     * would be wider or taller than a u16 allows) */
+    let rule = decode_rule(rle)?;
+    let mut board = board_around(&decode(rle)?, rule);
+    for _ in 0..generations {
+        let at_edge = board
+            .bounding_box()
+            .is_some_and(|(x0, y0, x1, y1)| x0 == 0 || y0 == 0 || x1 == board.width - 1 || y1 == board.height - 1);
+        if at_edge {
+            board = board_around(&board.live_pattern(), rule);
+        }
+        board.step();
+    }
+    Ok(encode_pattern(&board.live_pattern(), &rule))
+}
+
+fn board_around(pattern: &Pattern, rule: Rule) -> CellMatrix {
+    /* a dead board just big enough for the pattern and MARGIN around it, with it stamped in */
+    let width = pattern.width.saturating_add(2 * MARGIN);
+    let height = pattern.height.saturating_add(2 * MARGIN);
+    let mut board = CellMatrixBuilder::default()
+        .cell_size(1)
+        .rule(rule)
+        .boundary(BoundaryMode::Dead)
+        .build(vec2(width as f32, height as f32));
+    board.stamp(pattern, MARGIN, MARGIN);
+    board
+}
+
+pub fn run(args: &[String]) -> Result<String, ConbhuideError> {
+    /* read the file named in args and evolve it as evolve_rle does */
+    let args = parse_args(args)?;
+    let rle = std::fs::read_to_string(&args.input)
+        .map_err(|e| ConbhuideError::AssetLoad(format!("Couldn't read {}: {}", args.input, e)))?;
+    evolve_rle(&rle, args.generations)
+}
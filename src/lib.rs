@@ -0,0 +1,24 @@
+/*
+ * Library half of the crate: the automata and knot logic, usable without opening a window.
+ * main.rs is the interactive frontend built on top of it.
+ */
+#![allow(clippy::manual_is_multiple_of)]
+pub mod app;
+pub mod celtic;
+pub mod clock;
+pub mod coords;
+pub mod cycle;
+pub mod edge;
+pub mod error;
+pub mod headless;
+pub mod life;
+pub mod palette;
+pub mod patterns;
+pub mod rle;
+pub mod rng;
+pub mod rule;
+pub mod search;
+pub mod startup;
+pub mod strand;
+pub mod vector;
+pub mod view;
@@ -4,40 +4,683 @@ pub mod celtic;
 mod settings;
 use celtic::{draw_expr_for_tile, Cut, Offset, Tile};
  */
+use crate::clock::Stopwatch;
+use crate::coords::{grid_index, grid_to_screen, screen_to_grid_signed, CellPos};
+use crate::cycle::CycleWatch;
+use crate::error::ConbhuideError;
+use crate::rle::{self, Orientation, Pattern};
+use crate::rng::XorShift;
+use crate::rule::{Rule, WeightedRule};
 use macroquad::prelude::*;
 use macroquad::rand::gen_range;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
 //use settings::Settings;
 
+// QuadLife colors, indexed by cell state - 1
+const QUAD_COLORS: [Color; 4] = [RED, GREEN, BLUE, GOLD];
+// the eight cells around a cell (I am not a neighbor of myself)
+const NEIGHBOR_OFFSETS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+// boards with at least this many cells draw their live cells as meshes (see cell_meshes)
+const MESH_MIN_CELLS: usize = 64 * 64;
+// quads per mesh. macroquad's draw calls hold 5000 indices by default, at 6 to a quad
+const MESH_MAX_CELLS: usize = 800;
+// start of a board saved by to_bytes, then the version of the format it's in
+const BYTES_MAGIC: &[u8; 4] = b"CNBH";
+const BYTES_VERSION: u8 = 1;
+// 64 bit FNV-1a, for fingerprints that stay the same across platforms and Rust versions
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+const fn rgb(r: f32, g: f32, b: f32) -> Color {
+    Color { r, g, b, a: 1.0 }
+}
+
+// live cell colors for color_by_density, indexed by number of live neighbors:
+// isolated cells are dark blue, shading through green and yellow to red for crowded ones
+pub const DENSITY_COLORS: [Color; 9] = [
+    rgb(0.10, 0.10, 0.45),
+    rgb(0.10, 0.25, 0.75),
+    rgb(0.05, 0.55, 0.80),
+    rgb(0.10, 0.70, 0.45),
+    rgb(0.45, 0.80, 0.15),
+    rgb(0.85, 0.80, 0.10),
+    rgb(0.95, 0.55, 0.05),
+    rgb(0.90, 0.25, 0.05),
+    rgb(0.70, 0.05, 0.05),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LifeVariant {
+    #[default]
+    Conway,
+    // live cells carry one of four colors; newborns take the majority color of their parents
+    QuadLife,
+}
+
+pub fn quadlife_birth_color(parents: [u8; 3]) -> u8 {
+    /* color (1-4) of a cell born from three live parents: the majority color,
+     * or if all three differ, the one color none of them has */
+    let [a, b, c] = parents;
+    if a == b || a == c {
+        a
+    } else if b == c {
+        b
+    } else {
+        (1..=4).find(|color| !parents.contains(color)).unwrap_or(a)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BoundaryMode {
+    // everything off the edge of the grid is dead
+    #[default]
+    Dead,
+    // the grid is a torus: the left edge neighbors the right, the top the bottom
+    Wrap,
+    // the edges are mirrors: a cell just off the grid is a copy of the one just inside it,
+    // so patterns bounce back off the edges
+    Mirror,
+}
+
+impl BoundaryMode {
+    pub fn next(self) -> Self {
+        /* the mode after this one, coming back round to the first */
+        match self {
+            BoundaryMode::Dead => BoundaryMode::Wrap,
+            BoundaryMode::Wrap => BoundaryMode::Mirror,
+            BoundaryMode::Mirror => BoundaryMode::Dead,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CellShape {
+    #[default]
+    Square,
+    // inscribed in the cell
+    Circle,
+    // a square with its corners rounded off
+    Rounded,
+}
+
+fn reflect(pos: i32, len: u16) -> u16 {
+    /* pos reflected back onto 0..len across whichever edge it's past, e.g. -1 becomes 0
+     * and len becomes len - 1. Anything further off than len is clamped to the edge */
+    let len = len as i32;
+    let reflected = if pos < 0 {
+        -1 - pos
+    } else if pos >= len {
+        2 * len - 1 - pos
+    } else {
+        pos
+    };
+    reflected.clamp(0, len - 1) as u16
+}
+
+pub fn draw_cell_shape(shape: CellShape, top_left: Vec2, size: f32, color: Color) {
+    /* fill a size x size cell with the given shape */
+    match shape {
+        CellShape::Square => draw_rectangle(top_left.x, top_left.y, size, size, color),
+        CellShape::Circle => draw_circle(top_left.x + size / 2.0, top_left.y + size / 2.0, size / 2.0, color),
+        CellShape::Rounded => {
+            let radius = size / 4.0;
+            // a cross of two rectangles, with a quarter circle filling in each corner
+            draw_rectangle(top_left.x + radius, top_left.y, size - 2.0 * radius, size, color);
+            draw_rectangle(top_left.x, top_left.y + radius, size, size - 2.0 * radius, color);
+            for &(x, y) in &[(radius, radius), (size - radius, radius), (radius, size - radius), (size - radius, size - radius)] {
+                draw_circle(top_left.x + x, top_left.y + y, radius, color);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PaintMode {
+    // clicks bring cells to life and kill them
+    #[default]
+    Cells,
+    // clicks put up and take down walls
+    Walls,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FitMode {
+    // drop whatever doesn't fit on the board
+    #[default]
+    Clip,
+    // make the board bigger (with smaller cells) until the pattern fits
+    Grow,
+    // shrink the pattern to fit by keeping the top left cell of each block, see load_rle
+    ScaleDown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SymmetryAxis {
+    // mirror left/right
+    Horizontal,
+    // mirror top/bottom
+    Vertical,
+    Both,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /* one generation of a board, e.g. as yielded by CellMatrix::generations */
+    pub generation: u64,
+    pub population: usize,
+    // row-major, as for rle::encode
+    pub cells: Vec<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cycle {
+    /* a board that repeats itself every `period` generations, shifted by dx,dy.
+     * a still life has period 1, an oscillator no shift, and a spaceship a shift */
+    pub period: usize,
+    pub dx: i32,
+    pub dy: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emitter {
+    /* stamps pattern with its top left at pos every `period` generations, whatever the
+     * speed the board is running at. Flip the pattern to aim what it emits */
+    pub pos: (u16, u16),
+    pub pattern: Pattern,
+    // at least 1; private so it can't be set to 0, which would divide by zero in step
+    period: u64,
+    pub last_fired_gen: u64,
+}
+
+impl Emitter {
+    pub fn new(pos: (u16, u16), pattern: Pattern, period: u64) -> Self {
+        Emitter {
+            pos,
+            pattern,
+            period: period.max(1),
+            last_fired_gen: 0,
+        }
+    }
+
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Dimensions {
+    /* grid size in cells (or tiles) and where it sits on screen */
+    pub width: u16,
+    pub height: u16,
+    pub cell_size: u16,
+    pub offset: Vec2,
+}
+
+pub struct CellMatrixBuilder {
+    /* optional settings for a CellMatrix, e.g.
+     *   CellMatrixBuilder::default().cell_size(10).boundary(BoundaryMode::Wrap).build(screen_size) */
+    cell_size: u16,
+    offset: Option<Vec2>,
+    rule: Rule,
+    boundary: BoundaryMode,
+    colors: (Color, Color),
+    density: Option<f32>,
+}
+
+impl Default for CellMatrixBuilder {
+    fn default() -> Self {
+        CellMatrixBuilder {
+            cell_size: 25,
+            offset: None,
+            rule: Rule::default(),
+            boundary: BoundaryMode::default(),
+            colors: (BLACK, WHITE),
+            density: None,
+        }
+    }
+}
+
+impl CellMatrixBuilder {
+    pub fn cell_size(mut self, cell_size: u16) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn offset(mut self, offset: Vec2) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    pub fn boundary(mut self, boundary: BoundaryMode) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn colors(mut self, alive: Color, dead: Color) -> Self {
+        self.colors = (alive, dead);
+        self
+    }
+
+    pub fn density(mut self, density: f32) -> Self {
+        /* randomize the board at this density when it's built */
+        self.density = Some(density);
+        self
+    }
+
+    pub fn build(self, screen_size: Vec2) -> CellMatrix {
+        let mut cell_matrix = CellMatrix::new(screen_size, self.cell_size, self.offset);
+        cell_matrix.rule = self.rule;
+        cell_matrix.boundary = self.boundary;
+        cell_matrix.alive_color = self.colors.0;
+        cell_matrix.dead_color = self.colors.1;
+        if self.density.is_some() {
+            cell_matrix.randomize(self.density);
+        }
+        cell_matrix
+    }
+}
+
+// everything besides a cell's color that decides how it's drawn: width, height, cell_size,
+// cell_shape and cell_inset (as bits, to compare exactly)
+type CellLook = (u16, u16, u16, CellShape, u32);
+
 pub struct CellMatrix {
     pub width: u16,
     pub height: u16,
     cell_size: u16,
     screen_size: Vec2,
     cells: Vec<bool>,
-    frame_top_left: Vec2
+    // the generation before this one, for fading between the two
+    previous_cells: Vec<bool>,
+    previous_colors: Vec<u8>,
+    // QuadLife state per cell: 0 is dead, 1-4 are colors. Unused for Conway
+    colors: Vec<u8>,
+    // walls never change and are never alive, so to their neighbors they're just dead cells
+    walls: Vec<bool>,
+    // for each cell, how many generations step_n has left it alive, for render_exposure
+    exposure: Vec<u16>,
+    // for each cell, generations it has survived since it was born, for persistence
+    ages: Vec<u16>,
+    variant: LifeVariant,
+    frame_top_left: Vec2,
+    last_density: f32,
+    // steps taken since the board was last seeded or loaded
+    generation: u64,
+    // earlier generations, oldest first, for step_back
+    recorded: VecDeque<(Vec<bool>, Vec<u8>, Vec<u16>)>,
+    // how many earlier generations step keeps in recorded. 0 turns recording off
+    pub record_limit: usize,
+    // called after every step with the generation number and population
+    on_step: Option<Box<dyn FnMut(u64, usize)>>,
+    // time step and draw, for a profiler overlay
+    pub profile: bool,
+    last_step_us: u64,
+    last_draw_us: Cell<u64>,
+    // keep the board drawn on a render target and only draw the cells that change (see
+    // draw_cached). Only used while draw_dead is on
+    pub cache_draws: bool,
+    draw_target: RefCell<Option<RenderTarget>>,
+    // how the cells on draw_target were drawn, for take_dirty_cells
+    drawn: RefCell<Option<(CellLook, Vec<Color>)>>,
+    pub paint_symmetry: Option<SymmetryAxis>,
+    pub paint_mode: PaintMode,
+    pub boundary: BoundaryMode,
+    pub rule: Rule,
+    // weighted life: when set, neighbors are added up by weight and this is used instead of rule.
+    // Left as None the usual integer counts are kept, which is quicker
+    pub weighted_rule: Option<WeightedRule>,
+    // sticky cells: once a cell has survived this many generations it takes one more neighbor
+    // to crowd it to death, i.e. it survives n neighbors if rule lets it survive n - 1. Under
+    // rules whose survival counts have gaps (e.g. S1357) that fills the gaps in too. Loneliness
+    // kills old cells as usual, and the weighted rule ignores this
+    pub persistence: Option<u16>,
+    // stamp guns, fired by step
+    pub emitters: Vec<Emitter>,
+    pub alive_color: Color,
+    // color live cells by how crowded they are (see DENSITY_COLORS) instead of alive_color
+    pub color_by_density: bool,
+    pub dead_color: Color,
+    // draw dead cells in dead_color. Turned off they're left undrawn, so whatever is behind the
+    // board shows through, and cells fading out fade to transparent
+    pub draw_dead: bool,
+    pub wall_color: Color,
+    pub cell_shape: CellShape,
+    // pixels left undrawn on each side of a cell, so the background shows between cells
+    pub cell_inset: f32,
+    // outline the live cells' bounding box when drawing, e.g. to see whether a pattern grows
+    pub show_bounding_box: bool,
+    pub bounding_box_color: Color,
 }
 impl CellMatrix {
     pub fn new(screen_size: Vec2, cell_size: u16, frame_top_left: Option<Vec2>) -> Self {
-        // subtract 1 from width b/c we are offsetting alternate rows
+        /* as many cells as fit on the screen, and always at least one */
+        let cell_size = cell_size.max(1);
         let width: u16 = (screen_size.x / cell_size as f32) as u16;
         let height: u16 = (screen_size.y / cell_size as f32) as u16;
+        let mut cell_matrix = Self::with_dimensions(width, height, cell_size, frame_top_left);
+        cell_matrix.screen_size = screen_size;
+        cell_matrix
+    }
+
+    pub fn with_dimensions(width: u16, height: u16, cell_size: u16, frame_top_left: Option<Vec2>) -> Self {
+        /* a fixed width x height grid, whatever the screen size.
+         * use fit_to_screen to scale it to the window */
+        // an empty grid has no cells to index or draw, so make it at least 1x1
+        let (width, height, cell_size) = (width.max(1), height.max(1), cell_size.max(1));
         CellMatrix {
             width,
             height,
             cell_size,
-            screen_size,
-            cells: vec![false; (width * height) as usize],
-            frame_top_left: frame_top_left.unwrap_or(vec2(0.,0.))
+            screen_size: vec2(width as f32 * cell_size as f32, height as f32 * cell_size as f32),
+            cells: vec![false; width as usize * height as usize],
+            previous_cells: vec![false; width as usize * height as usize],
+            previous_colors: vec![0; width as usize * height as usize],
+            colors: vec![0; width as usize * height as usize],
+            walls: vec![false; width as usize * height as usize],
+            exposure: vec![0; width as usize * height as usize],
+            ages: vec![0; width as usize * height as usize],
+            variant: LifeVariant::Conway,
+            frame_top_left: frame_top_left.unwrap_or(vec2(0.,0.)),
+            last_density: 0.2,
+            generation: 0,
+            recorded: VecDeque::new(),
+            record_limit: 0,
+            on_step: None,
+            profile: false,
+            last_step_us: 0,
+            last_draw_us: Cell::new(0),
+            cache_draws: false,
+            draw_target: RefCell::new(None),
+            drawn: RefCell::new(None),
+            paint_symmetry: None,
+            paint_mode: PaintMode::Cells,
+            boundary: BoundaryMode::Dead,
+            rule: Rule::conway(),
+            weighted_rule: None,
+            persistence: None,
+            emitters: vec![],
+            alive_color: BLACK,
+            color_by_density: false,
+            dead_color: WHITE,
+            draw_dead: true,
+            wall_color: DARKGRAY,
+            cell_shape: CellShape::Square,
+            cell_inset: 0.0,
+            show_bounding_box: false,
+            bounding_box_color: MAGENTA,
+        }
+    }
+
+    pub fn from_cells(
+        width: u16,
+        height: u16,
+        cells: Vec<bool>,
+        cell_size: u16,
+        frame_top_left: Option<Vec2>,
+    ) -> Result<Self, ConbhuideError> {
+        /* a width x height board with the given cells alive, in rows from the top left */
+        if width == 0 || height == 0 {
+            return Err(ConbhuideError::InvalidDimensions(format!(
+                "A board needs at least one cell, not {}x{}",
+                width, height
+            )));
+        }
+        if cells.len() != width as usize * height as usize {
+            return Err(ConbhuideError::InvalidDimensions(format!(
+                "{} cells don't make a {}x{} board",
+                cells.len(),
+                width,
+                height
+            )));
+        }
+        let mut cell_matrix = Self::with_dimensions(width, height, cell_size, frame_top_left);
+        cell_matrix.cells = cells;
+        cell_matrix.recolor();
+        Ok(cell_matrix)
+    }
+
+    pub fn from_rows(rows: &[&[bool]], cell_size: u16, frame_top_left: Option<Vec2>) -> Result<Self, ConbhuideError> {
+        /* as from_cells, with the board's size taken from the rows, which must all be as long */
+        let width = rows.first().map_or(0, |row| row.len());
+        if let Some(row) = rows.iter().position(|row| row.len() != width) {
+            return Err(ConbhuideError::InvalidDimensions(format!(
+                "Row {} is {} cells long, but the first is {}",
+                row,
+                rows[row].len(),
+                width
+            )));
+        }
+        if width > u16::MAX as usize || rows.len() > u16::MAX as usize {
+            return Err(ConbhuideError::InvalidDimensions(format!(
+                "A board can be at most {} cells across, not {}x{}",
+                u16::MAX,
+                width,
+                rows.len()
+            )));
+        }
+        Self::from_cells(width as u16, rows.len() as u16, rows.concat(), cell_size, frame_top_left)
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        /* change the grid size, keeping whatever is in the overlap with the old grid.
+         * as with with_dimensions, it's at least 1x1 */
+        let (width, height) = (width.max(1), height.max(1));
+        let mut resized = CellMatrix::with_dimensions(width, height, self.cell_size, None);
+        for y in 0..self.height.min(height) {
+            for x in 0..self.width.min(width) {
+                let (from, to) = (self.ind_for_pos(x, y), resized.ind_for_pos(x, y));
+                resized.cells[to] = self.cells[from];
+                resized.colors[to] = self.colors[from];
+                resized.previous_cells[to] = self.previous_cells[from];
+                resized.previous_colors[to] = self.previous_colors[from];
+                resized.walls[to] = self.walls[from];
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.cells = resized.cells;
+        self.colors = resized.colors;
+        self.previous_cells = resized.previous_cells;
+        self.previous_colors = resized.previous_colors;
+        self.walls = resized.walls;
+        self.exposure = resized.exposure;
+        self.ages = resized.ages;
+        // recorded generations are the old size
+        self.recorded.clear();
+    }
+
+    pub fn fit_to_screen(&mut self, screen_size: Vec2) {
+        /* pick the largest cell size that shows the whole grid in screen_size */
+        let fit_x = screen_size.x / self.width.max(1) as f32;
+        let fit_y = screen_size.y / self.height.max(1) as f32;
+        self.cell_size = (fit_x.min(fit_y) as u16).max(1);
+        self.screen_size = screen_size;
+    }
+
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions {
+            width: self.width,
+            height: self.height,
+            cell_size: self.cell_size,
+            offset: self.frame_top_left,
         }
     }
 
     pub fn randomize(&mut self, living_fraction: Option<f32>) {
-        /* Add random live cells at rate living_fraction */
+        /* Add random live cells at rate living_fraction.
+         * With None, reuse whichever fraction was last asked for */
+        self.fill_random(living_fraction, &mut XorShift::from_time());
+    }
+
+    pub fn randomize_seeded(&mut self, living_fraction: Option<f32>, seed: u64) {
+        /* as randomize, but the same seed always gives the same board, whatever the platform */
+        self.fill_random(living_fraction, &mut XorShift::new(seed));
+    }
+
+    pub fn randomize_min(&mut self, min_cells: usize) {
+        /* as randomize with the last fraction asked for, then bring random dead cells to life
+         * until at least min_cells are alive, or there are no cells left that can be */
+        self.fill_random_min(min_cells, &mut XorShift::from_time());
+    }
+
+    pub fn randomize_min_seeded(&mut self, min_cells: usize, seed: u64) {
+        self.fill_random_min(min_cells, &mut XorShift::new(seed));
+    }
+
+    fn fill_random_min(&mut self, min_cells: usize, rng: &mut XorShift) {
+        self.fill_random(None, rng);
+        let mut dead: Vec<usize> = (0..self.cells.len()).filter(|&ind| !self.cells[ind] && !self.walls[ind]).collect();
+        for _ in 0..min_cells.saturating_sub(self.population()).min(dead.len()) {
+            let ind = dead.swap_remove(rng.below(dead.len() as u32) as usize);
+            self.cells[ind] = true;
+            self.colors[ind] = rng.below(4) as u8 + 1;
+        }
+    }
+
+    pub fn randomize_with<F: Fn(u16, u16) -> f32>(&mut self, prob: F) {
+        /* as randomize, but each cell x,y comes alive with chance prob(x, y), so some parts of
+         * the board can be seeded more thickly than others */
+        self.fill_random_with(prob, &mut XorShift::from_time());
+    }
+
+    pub fn randomize_with_seeded<F: Fn(u16, u16) -> f32>(&mut self, prob: F, seed: u64) {
+        self.fill_random_with(prob, &mut XorShift::new(seed));
+    }
+
+    fn fill_random_with<F: Fn(u16, u16) -> f32>(&mut self, prob: F, rng: &mut XorShift) {
+        self.restart_generations();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let ind = self.ind_for_pos(x, y);
+                // next_f32 is below 1, so a chance of 1 or more always comes alive
+                self.cells[ind] = rng.next_f32() < prob(x, y);
+                self.colors[ind] = if self.cells[ind] { rng.below(4) as u8 + 1 } else { 0 };
+            }
+        }
+        self.kill_walled_cells();
+    }
+
+    pub fn randomize_region(&mut self, a: Vec2, b: Vec2, living_fraction: Option<f32>) {
+        /* as randomize, but only for the cells in the rectangle with screen corners a and b.
+         * Everything outside it is left alone */
+        self.randomize_region_with(a, b, living_fraction, &mut XorShift::from_time());
+    }
+
+    pub fn randomize_region_seeded(&mut self, a: Vec2, b: Vec2, living_fraction: Option<f32>, seed: u64) {
+        self.randomize_region_with(a, b, living_fraction, &mut XorShift::new(seed));
+    }
+
+    fn randomize_region_with(&mut self, a: Vec2, b: Vec2, living_fraction: Option<f32>, rng: &mut XorShift) {
+        if let Some((top_left, bottom_right)) = self.region_on_grid(a, b) {
+            self.fill_random_region(top_left, bottom_right, living_fraction, rng);
+        }
+    }
+
+    fn region_on_grid(&self, a: Vec2, b: Vec2) -> Option<((u16, u16), (u16, u16))> {
+        /* top left and bottom right cells of the part of the rectangle with screen corners a
+         * and b that's on the grid, or None if none of it is */
+        let (a_x, a_y) = screen_to_grid_signed(a, self.cell_size, self.frame_top_left);
+        let (b_x, b_y) = screen_to_grid_signed(b, self.cell_size, self.frame_top_left);
+        let (x0, x1) = (a_x.min(b_x).max(0), a_x.max(b_x).min(self.width as i32 - 1));
+        let (y0, y1) = (a_y.min(b_y).max(0), a_y.max(b_y).min(self.height as i32 - 1));
+        if x0 > x1 || y0 > y1 {
+            return None;
+        }
+        Some(((x0 as u16, y0 as u16), (x1 as u16, y1 as u16)))
+    }
+
+    pub fn extract_pattern(&self, a: Vec2, b: Vec2, name: &str) -> Pattern {
+        /* the live cells in the rectangle with screen corners a and b, relative to its top left,
+         * e.g. to save as a pattern called name. The pattern is the size of the part of the
+         * rectangle on the grid, so stamping it back at that top left puts the same cells
+         * back. A rectangle entirely off the grid gives an empty pattern */
+        let ((x0, y0), (x1, y1)) = match self.region_on_grid(a, b) {
+            Some(region) => region,
+            None => {
+                return Pattern {
+                    width: 0,
+                    height: 0,
+                    cells: vec![],
+                }
+            }
+        };
+        let pattern = Pattern {
+            width: x1 - x0 + 1,
+            height: y1 - y0 + 1,
+            cells: (y0..=y1)
+                .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+                .filter(|&(x, y)| self.cell_is_alive(x, y))
+                .map(|(x, y)| (x - x0, y - y0))
+                .collect(),
+        };
+        info!(
+            "Extracted {}: {} live cells in {} by {} from {},{}",
+            name,
+            pattern.cells.len(),
+            pattern.width,
+            pattern.height,
+            x0,
+            y0
+        );
+        pattern
+    }
+
+    fn fill_random(&mut self, living_fraction: Option<f32>, rng: &mut XorShift) {
+        self.restart_generations();
+        self.fill_random_region((0, 0), (self.width - 1, self.height - 1), living_fraction, rng);
+    }
+
+    fn fill_random_region(&mut self, from: (u16, u16), to: (u16, u16), living_fraction: Option<f32>, rng: &mut XorShift) {
+        /* randomize the cells from one corner to the other, inclusive */
+        if let Some(fraction) = living_fraction {
+            self.last_density = fraction;
+        }
+        for y in from.1..=to.1 {
+            for x in from.0..=to.0 {
+                let ind = self.ind_for_pos(x, y);
+                self.cells[ind] = rng.next_f32() < self.last_density;
+                self.colors[ind] = if self.cells[ind] { rng.below(4) as u8 + 1 } else { 0 };
+            }
+        }
+        self.kill_walled_cells();
+    }
+
+    pub fn variant(&self) -> LifeVariant {
+        self.variant
+    }
+
+    pub fn set_variant(&mut self, variant: LifeVariant) {
+        self.variant = variant;
+        self.recolor();
+    }
+
+    fn recolor(&mut self) {
+        /* give every live cell a random QuadLife color, and clear dead ones */
+        let mut rng = XorShift::from_time();
         for i in 0..self.cells.len() {
-            self.cells[i] = gen_range(0, (1.0 / living_fraction.unwrap_or(0.2)) as i32) == 0;
+            self.colors[i] = if self.cells[i] { rng.below(4) as u8 + 1 } else { 0 };
         }
     }
 
+    pub fn density(&self) -> f32 {
+        /* living fraction used by the most recent randomize */
+        self.last_density
+    }
+
+    pub fn is_alive(&self, pos: impl Into<CellPos>) -> bool {
+        /* whether a cell, as a CellPos or (x, y) tuple, is alive */
+        let pos = pos.into();
+        self.cell_is_alive(pos.x, pos.y)
+    }
+
     fn cell_is_alive(&self, x: u16, y: u16) -> bool {
         self.cells[self.ind_for_pos(x, y)]
     }
@@ -45,93 +688,925 @@ impl CellMatrix {
     fn ind_for_pos(&self, x: u16, y: u16) -> usize {
         /* return cell index for a given x,y coordinate
          * (cells are stored in a 1d vector) */
-        (y * self.width) as usize + x as usize
+        grid_index(x, y, self.width)
+    }
+
+    fn resolve_pos(&self, x: i32, y: i32) -> Option<(u16, u16)> {
+        /* the cell at x,y, which may be off the grid, given the boundary mode.
+         * None means it's off the grid and counts as dead */
+        match self.boundary {
+            BoundaryMode::Dead => {
+                if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+                    None
+                } else {
+                    Some((x as u16, y as u16))
+                }
+            }
+            BoundaryMode::Wrap => Some((
+                x.rem_euclid(self.width as i32) as u16,
+                y.rem_euclid(self.height as i32) as u16,
+            )),
+            BoundaryMode::Mirror => Some((reflect(x, self.width), reflect(y, self.height))),
+        }
     }
 
-    fn cell_pos_for_click(&self, screen_pos: Vec2) -> (u16, u16) {
-        /* translate a click on the screen to a cell position */
+    pub fn cell_pos_for_click(&self, screen_pos: Vec2) -> Option<(u16, u16)> {
+        /* translate a click on the screen to a cell position.
+         * clicks past the edge of the grid wrap round in Wrap mode, and are ignored otherwise */
         info!("Screen position {},{}", screen_pos.x, screen_pos.y,);
-        (
-            (screen_pos.x - self.frame_top_left.x) as u16 / self.cell_size,
-            (screen_pos.y - self.frame_top_left.y) as u16 / self.cell_size,
-        )
+        let (x, y) = screen_to_grid_signed(screen_pos, self.cell_size, self.frame_top_left);
+        if self.boundary == BoundaryMode::Mirror && (x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32) {
+            // a reflection of a cell isn't the cell itself
+            return None;
+        }
+        self.resolve_pos(x, y)
     }
 
     pub fn draw(&self) {
-        for y in 0..self.height as u16 {
-            for x in 0..self.width as u16 {
-                self.draw_cell(x, y);
+        self.draw_blended(1.0);
+    }
+
+    pub fn draw_blended(&self, fraction: f32) {
+        /* draw the board part way between the previous generation (fraction 0)
+         * and the current one (fraction 1), so cells fade in and out */
+        let stopwatch = self.profile.then(Stopwatch::start);
+        let n_cells = self.width as usize * self.height as usize;
+        if self.cache_draws && self.draw_dead {
+            self.draw_cached(fraction);
+        } else if n_cells >= MESH_MIN_CELLS && self.cell_shape == CellShape::Square && self.cell_inset == 0.0 {
+            // one rectangle for all the dead cells, and a few meshes for the rest
+            if self.draw_dead {
+                let size = vec2(self.width as f32, self.height as f32) * self.cell_size as f32;
+                draw_rectangle(self.frame_top_left.x, self.frame_top_left.y, size.x, size.y, self.dead_color);
+            }
+            for mesh in self.cell_meshes(fraction) {
+                draw_mesh(&mesh);
+            }
+        } else {
+            for (x, y) in self.drawn_cells(fraction) {
+                self.draw_cell(x, y, fraction);
+            }
+        }
+        if let (true, Some(rect)) = (self.show_bounding_box, self.bounding_box_rect()) {
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, self.bounding_box_color);
+        }
+        if let Some(stopwatch) = stopwatch {
+            self.last_draw_us.set(stopwatch.elapsed_us());
+        }
+    }
+
+    pub fn take_dirty_cells(&self, fraction: f32) -> Vec<(u16, u16)> {
+        /* the cells whose color (fraction of the way through fading, as in draw_blended) has
+         * changed since this was last called, and takes note of them as drawn. The first call,
+         * or one after anything else about how cells look has changed (a resize, a new cell
+         * shape), gives every cell. A new theme changes the colors, so it gives them all too */
+        let look = self.cell_look();
+        let mut drawn = self.drawn.borrow_mut();
+        let everything = drawn.as_ref().map(|(drawn_look, _)| *drawn_look) != Some(look);
+        if everything {
+            *drawn = Some((look, vec![BLANK; self.cells.len()]));
+        }
+        let colors = &mut drawn.as_mut().unwrap().1;
+        let mut dirty = vec![];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (ind, color) = (self.ind_for_pos(x, y), self.blended_color(x, y, fraction));
+                if everything || colors[ind] != color {
+                    colors[ind] = color;
+                    dirty.push((x, y));
+                }
+            }
+        }
+        dirty
+    }
+
+    fn cell_look(&self) -> CellLook {
+        (self.width, self.height, self.cell_size, self.cell_shape, self.cell_inset.to_bits())
+    }
+
+    fn draw_cached(&self, fraction: f32) {
+        /* draw the board from a render target kept from frame to frame, drawing onto it just
+         * the cells that have changed since the last frame. macroquad clears the screen every
+         * frame, but not render targets, so the unchanged cells are still there to copy across
+         * in one go. A resize needs a new target, which starts out with everything to draw */
+        let size = vec2(self.width as f32, self.height as f32) * self.cell_size as f32;
+        let mut draw_target = self.draw_target.borrow_mut();
+        let target_size = draw_target.as_ref().map(|target| target.texture.size());
+        if target_size != Some(size) {
+            let target = render_target(size.x as u32, size.y as u32);
+            target.texture.set_filter(FilterMode::Nearest);
+            *draw_target = Some(target);
+            self.drawn.replace(None);
+        }
+        let target = draw_target.as_ref().unwrap();
+        push_camera_state();
+        let mut camera = Camera2D::from_display_rect(Rect::new(self.frame_top_left.x, self.frame_top_left.y, size.x, size.y));
+        camera.render_target = Some(target.clone());
+        set_camera(&camera);
+        if self.drawn.borrow().as_ref().map(|(look, _)| *look) != Some(self.cell_look()) {
+            // everything is about to be drawn again. Let the screen show through any gaps
+            // between cells, as it does without a cache
+            clear_background(Color::new(0.0, 0.0, 0.0, 0.0));
+        }
+        for (x, y) in self.take_dirty_cells(fraction) {
+            self.draw_cell(x, y, fraction);
+        }
+        pop_camera_state();
+        // render targets come out upside down
+        let params = DrawTextureParams {
+            dest_size: Some(size),
+            flip_y: true,
+            ..Default::default()
+        };
+        draw_texture_ex(&target.texture, self.frame_top_left.x, self.frame_top_left.y, WHITE, params);
+    }
+
+    pub fn drawn_cells(&self, fraction: f32) -> Vec<(u16, u16)> {
+        /* the cells draw_blended draws one by one: all of them, or without draw_dead just
+         * those with something to show */
+        let mut drawn = vec![];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.draw_dead || self.blended_color(x, y, fraction).a > 0.0 {
+                    drawn.push((x, y));
+                }
+            }
+        }
+        drawn
+    }
+
+    pub fn cell_meshes(&self, fraction: f32) -> Vec<Mesh> {
+        /* a square quad for each cell not drawn in dead_color (as in draw_blended), split into
+         * meshes of at most MESH_MAX_CELLS quads so each can be drawn in a single call.
+         * Cells are always square here, whatever cell_shape says */
+        let empty = || Mesh {
+            vertices: vec![],
+            indices: vec![],
+            texture: None,
+        };
+        let mut meshes = vec![];
+        let mut mesh = empty();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let color = self.blended_color(x, y, fraction);
+                if color == self.state_color(false, 0) {
+                    continue;
+                }
+                if mesh.vertices.len() == 4 * MESH_MAX_CELLS {
+                    meshes.push(std::mem::replace(&mut mesh, empty()));
+                }
+                let rect = self.cell_rect(x, y);
+                let first = mesh.vertices.len() as u16;
+                let corners = [
+                    rect.point(),
+                    vec2(rect.right(), rect.y),
+                    vec2(rect.right(), rect.bottom()),
+                    vec2(rect.x, rect.bottom()),
+                ];
+                for corner in corners.iter() {
+                    mesh.vertices.push(Vertex::new(corner.x, corner.y, 0.0, 0.0, 0.0, color));
+                }
+                mesh.indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
             }
         }
+        if !mesh.vertices.is_empty() {
+            meshes.push(mesh);
+        }
+        meshes
+    }
+
+    pub fn last_step_us(&self) -> u64 {
+        /* microseconds the latest step took, while profiling */
+        self.last_step_us
+    }
+
+    pub fn last_draw_us(&self) -> u64 {
+        /* microseconds the latest draw took, while profiling */
+        self.last_draw_us.get()
+    }
+
+    fn state_color(&self, alive: bool, color: u8) -> Color {
+        match (alive, self.variant) {
+            (false, _) if self.draw_dead => self.dead_color,
+            (false, _) => Color::new(self.dead_color.r, self.dead_color.g, self.dead_color.b, 0.0),
+            (true, LifeVariant::Conway) => self.alive_color,
+            (true, LifeVariant::QuadLife) => QUAD_COLORS[(color as usize).clamp(1, 4) - 1],
+        }
+    }
+
+    fn live_neighbor_positions(&self, x: u16, y: u16) -> impl Iterator<Item = (u16, u16)> + '_ {
+        /* the live cells among the eight around x,y. Positions off the grid are wrapped
+         * round or, with a Dead boundary, skipped, so nothing out of range is ever looked at */
+        NEIGHBOR_OFFSETS
+            .iter()
+            .filter_map(move |&(i, j)| self.resolve_pos(x as i32 + i, y as i32 + j))
+            .filter(move |&(n_x, n_y)| self.cell_is_alive(n_x, n_y))
+    }
+
+    pub fn live_neighbors(&self, x: u16, y: u16) -> usize {
+        /* how many of the eight cells around x,y are alive, given the boundary mode */
+        self.live_neighbor_positions(x, y).count()
+    }
+
+    pub fn weighted_neighbors(&self, x: u16, y: u16) -> f32 {
+        /* the live cells around x,y added up by weighted_rule's weights. Without a weighted rule
+         * every neighbor counts for one, the same as live_neighbors */
+        NEIGHBOR_OFFSETS
+            .iter()
+            .filter_map(|&(i, j)| self.resolve_pos(x as i32 + i, y as i32 + j).map(|pos| (i, j, pos)))
+            .filter(|&(_, _, (n_x, n_y))| self.cell_is_alive(n_x, n_y))
+            .map(|(i, j, _)| self.weighted_rule.as_ref().map_or(1.0, |rule| rule.weight(i, j)))
+            .sum()
+    }
+
+    pub fn cell_color(&self, x: u16, y: u16) -> Color {
+        /* color a cell is drawn in for the current generation */
+        let ind = self.ind_for_pos(x, y);
+        if self.walls[ind] {
+            self.wall_color
+        } else if self.color_by_density && self.cells[ind] {
+            DENSITY_COLORS[self.live_neighbors(x, y)]
+        } else {
+            self.state_color(self.cells[ind], self.colors[ind])
+        }
     }
 
-    fn draw_cell(&self, x: u16, y: u16) {
+    pub fn blended_color(&self, x: u16, y: u16, fraction: f32) -> Color {
+        /* color of a cell, fraction of the way from its previous state to its current one */
+        let ind = self.ind_for_pos(x, y);
+        let current = self.cell_color(x, y);
+        if fraction >= 1.0 || self.walls[ind] {
+            return current;
+        }
+        let previous = self.state_color(self.previous_cells[ind], self.previous_colors[ind]);
+        let mix = |from: f32, to: f32| from + (to - from) * fraction.max(0.0);
+        Color::new(
+            mix(previous.r, current.r),
+            mix(previous.g, current.g),
+            mix(previous.b, current.b),
+            mix(previous.a, current.a),
+        )
+    }
+
+    pub fn set_cell_inset(&mut self, inset: f32) {
+        /* leave a gap of inset pixels round each cell. Cells never shrink below one pixel */
+        self.cell_inset = inset.clamp(0.0, (self.cell_size as f32 - 1.0).max(0.0) / 2.0);
+    }
+
+    pub fn cell_rect(&self, x: u16, y: u16) -> Rect {
+        /* the square a cell is drawn in: the whole cell, less cell_inset all round */
+        let top_left = grid_to_screen(x, y, self.cell_size, self.frame_top_left);
+        let size = self.cell_size as f32;
+        let inset = self.cell_inset.clamp(0.0, (size - 1.0).max(0.0) / 2.0);
+        Rect::new(top_left.x + inset, top_left.y + inset, size - 2.0 * inset, size - 2.0 * inset)
+    }
+
+    fn draw_cell(&self, x: u16, y: u16, fraction: f32) {
         /* draw a rectangle for a given cell reference at the appropriate place in the image*/
-        draw_rectangle(
-            self.frame_top_left.x + ((x * self.cell_size) as f32),
-            self.frame_top_left.y + ((y * self.cell_size) as f32),
-            self.cell_size.into(),
-            self.cell_size.into(),
-            if self.cell_is_alive(x, y) {
-                BLACK
-            } else {
-                WHITE
-            },
-        );
+        let rect = self.cell_rect(x, y);
+        let (top_left, size) = (rect.point(), rect.w);
+        if self.cell_shape != CellShape::Square && self.draw_dead {
+            // fill in round the shape
+            draw_cell_shape(CellShape::Square, top_left, size, self.dead_color);
+        }
+        draw_cell_shape(self.cell_shape, top_left, size, self.blended_color(x, y, fraction));
+    }
+
+    pub fn mirror_positions(&self, x: u16, y: u16, axis: Option<SymmetryAxis>) -> Vec<(u16, u16)> {
+        /* x,y plus its reflections across the centre of the grid on the given axes,
+         * without duplicates (a cell on the centre line is its own mirror) */
+        let mirror_x = self.width - 1 - x;
+        let mirror_y = self.height - 1 - y;
+        let mut positions = match axis {
+            None => vec![(x, y)],
+            Some(SymmetryAxis::Horizontal) => vec![(x, y), (mirror_x, y)],
+            Some(SymmetryAxis::Vertical) => vec![(x, y), (x, mirror_y)],
+            Some(SymmetryAxis::Both) => vec![(x, y), (mirror_x, y), (x, mirror_y), (mirror_x, mirror_y)],
+        };
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+
+    pub fn clear(&mut self) {
+        /* kill every cell and start counting generations afresh */
+        self.cells.iter_mut().for_each(|cell| *cell = false);
+        self.colors.iter_mut().for_each(|color| *color = 0);
+        self.restart_generations();
+    }
+
+    pub fn stamp(&mut self, pattern: &Pattern, x: u16, y: u16) {
+        /* bring a pattern's live cells to life with its top left corner at x,y.
+         * cells off the board are dropped, even when wrapping */
+        for &(p_x, p_y) in &pattern.cells {
+            let (c_x, c_y) = (x as u32 + p_x as u32, y as u32 + p_y as u32);
+            if c_x < self.width as u32 && c_y < self.height as u32 {
+                let ind = self.ind_for_pos(c_x as u16, c_y as u16);
+                self.cells[ind] = true;
+                self.colors[ind] = gen_range(1, 5);
+            }
+        }
+        self.kill_walled_cells();
+    }
+
+    pub fn shift(&mut self, dx: i16, dy: i16) {
+        /* move every live cell dx across and dy down. Cells moved off the grid wrap round in
+         * Wrap mode and are dropped otherwise. Walls stay put, and kill any cell moved onto them */
+        let mut cells = vec![false; self.cells.len()];
+        let mut colors = vec![0; self.colors.len()];
+        let mut ages = vec![0; self.ages.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let from = self.ind_for_pos(x, y);
+                if !self.cells[from] {
+                    continue;
+                }
+                let (to_x, to_y) = (x as i32 + dx as i32, y as i32 + dy as i32);
+                let to = if self.boundary == BoundaryMode::Wrap {
+                    Some((to_x.rem_euclid(self.width as i32) as u16, to_y.rem_euclid(self.height as i32) as u16))
+                } else if to_x < 0 || to_y < 0 || to_x >= self.width as i32 || to_y >= self.height as i32 {
+                    None
+                } else {
+                    Some((to_x as u16, to_y as u16))
+                };
+                if let Some((to_x, to_y)) = to {
+                    let to = self.ind_for_pos(to_x, to_y);
+                    cells[to] = true;
+                    colors[to] = self.colors[from];
+                    ages[to] = self.ages[from];
+                }
+            }
+        }
+        // nothing to fade from
+        self.previous_cells = cells.clone();
+        self.previous_colors = colors.clone();
+        self.cells = cells;
+        self.colors = colors;
+        self.ages = ages;
+        self.kill_walled_cells();
+    }
+
+    pub fn stamp_oriented(&mut self, pattern: &Pattern, x: u16, y: u16, orientation: Orientation) {
+        /* as stamp, with the pattern turned to orientation first */
+        self.stamp(&pattern.oriented(orientation), x, y);
+    }
+
+    pub fn set_cell_xy(&mut self, x: u16, y: u16, alive: bool) {
+        /* set a cell and, when painting symmetrically, its mirror images */
+        let color = if alive { gen_range(1, 5) } else { 0 };
+        self.paint_xy(x, y, alive, color);
+    }
+
+    pub fn toggle_cell_xy(&mut self, x: u16, y: u16) -> Result<(), ConbhuideError> {
+        /* flip a cell by position, e.g. one typed in rather than clicked */
+        if x >= self.width || y >= self.height {
+            return Err(ConbhuideError::OutOfBounds(format!(
+                "cell {},{} is off the {} by {} board",
+                x, y, self.width, self.height
+            )));
+        }
+        self.set_cell_xy(x, y, !self.cell_is_alive(x, y));
+        Ok(())
+    }
+
+    pub fn set_cell(&mut self, mouse_position: Vec2, alive: bool) {
+        if let Some((x, y)) = self.cell_pos_for_click(mouse_position) {
+            self.set_cell_xy(x, y, alive);
+        }
+    }
+
+    fn paint_xy(&mut self, x: u16, y: u16, alive: bool, color: u8) {
+        for (m_x, m_y) in self.mirror_positions(x, y, self.paint_symmetry) {
+            let ind = self.ind_for_pos(m_x, m_y);
+            // walls stay dead
+            self.cells[ind] = alive && !self.walls[ind];
+            self.colors[ind] = if self.cells[ind] { color } else { 0 };
+        }
+    }
+
+    pub fn is_wall(&self, pos: impl Into<CellPos>) -> bool {
+        let pos = pos.into();
+        self.walls[self.ind_for_pos(pos.x, pos.y)]
+    }
+
+    pub fn set_wall_xy(&mut self, x: u16, y: u16, wall: bool) {
+        /* put up or take down a wall and, when painting symmetrically, its mirror images.
+         * whatever was alive where a wall goes up dies */
+        for (m_x, m_y) in self.mirror_positions(x, y, self.paint_symmetry) {
+            let ind = self.ind_for_pos(m_x, m_y);
+            self.walls[ind] = wall;
+        }
+        self.kill_walled_cells();
+    }
+
+    pub fn clear_walls(&mut self) {
+        self.walls.iter_mut().for_each(|wall| *wall = false);
+    }
+
+    fn kill_walled_cells(&mut self) {
+        for ind in 0..self.walls.len() {
+            if self.walls[ind] {
+                self.cells[ind] = false;
+                self.colors[ind] = 0;
+                self.ages[ind] = 0;
+            }
+        }
     }
 
     pub fn flip_cell(&mut self, mouse_position: Vec2) {
-        let (x, y) = self.cell_pos_for_click(mouse_position);
+        if let Some((x, y)) = self.cell_pos_for_click(mouse_position) {
+            self.flip_cell_xy(x, y);
+        }
+    }
+
+    pub fn flip_cell_xy(&mut self, x: u16, y: u16) {
+        /* what a click on a cell does: flip it, or its wall when painting walls. QuadLife cells
+         * go through each color before dying */
         let cell_ind = self.ind_for_pos(x, y);
-        self.cells[cell_ind] = !self.cells[cell_ind];
+        if self.paint_mode == PaintMode::Walls {
+            self.set_wall_xy(x, y, !self.walls[cell_ind]);
+            info!("Called flip_cell on {},{}, making it a wall: {}", x, y, self.walls[cell_ind]);
+            return;
+        }
+        let (alive, color) = if self.variant == LifeVariant::QuadLife {
+            // cycle dead -> each color -> dead
+            let color = (self.colors[cell_ind] + 1) % 5;
+            (color != 0, color)
+        } else {
+            (!self.cells[cell_ind], if self.cells[cell_ind] { 0 } else { gen_range(1, 5) })
+        };
+        self.paint_xy(x, y, alive, color);
         info!(
             "Called flip_cell on {},{}, making it {}",
             x, y, self.cells[cell_ind]
         );
     }
 
+    pub fn to_rle(&self) -> String {
+        /* the whole board as run-length encoded text */
+        rle::encode(self.width, self.height, &self.cells, &self.rule)
+    }
+
+    pub fn to_png_1bpp(&self) -> Result<Vec<u8>, ConbhuideError> {
+        /* the whole board as a black and white PNG, one pixel per cell with live cells black.
+         * walls come out white like dead cells */
+        // a PNG can't be empty. The constructors make boards at least 1x1, but width and
+        // height are public
+        if self.width == 0 || self.height == 0 {
+            return Err(ConbhuideError::InvalidDimensions(format!(
+                "Can't save an empty {}x{} board as a PNG",
+                self.width, self.height
+            )));
+        }
+        // rows are packed 8 pixels to a byte, leftmost in the top bit, and padded to whole bytes
+        let row_bytes = (self.width as usize).div_ceil(8);
+        let mut data = vec![0xffu8; row_bytes * self.height as usize];
+        for y in 0..self.height {
+            for x in (0..self.width).filter(|&x| self.cell_is_alive(x, y)) {
+                data[y as usize * row_bytes + x as usize / 8] &= !(0x80 >> (x % 8));
+            }
+        }
+        let mut png_bytes = vec![];
+        let mut encoder = png::Encoder::new(&mut png_bytes, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::One);
+        // the encoder only fails on bad dimensions, data that doesn't fill them, or a failed
+        // write, and the dimensions are checked above, data is sized to them and a Vec takes
+        // every write
+        let mut writer = encoder.write_header().expect("board dimensions are non-zero");
+        writer.write_image_data(&data).expect("data is one packed row per board row");
+        writer.finish().expect("writing to a Vec can't fail");
+        Ok(png_bytes)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        /* the board in a compact binary form for saving or sending: BYTES_MAGIC, BYTES_VERSION,
+         * width and height as little-endian u16s, then the cells row by row, 8 to a byte with
+         * the first in the top bit. The last byte is padded with dead cells */
+        let mut bytes = BYTES_MAGIC.to_vec();
+        bytes.push(BYTES_VERSION);
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        for chunk in self.cells.chunks(8) {
+            bytes.push(chunk.iter().enumerate().fold(0, |byte, (i, &alive)| byte | ((alive as u8) << (7 - i))));
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8], cell_size: u16, frame_top_left: Option<Vec2>) -> Result<Self, ConbhuideError> {
+        /* a board saved by to_bytes */
+        let header_len = BYTES_MAGIC.len() + 5;
+        if bytes.len() < header_len || &bytes[..BYTES_MAGIC.len()] != BYTES_MAGIC {
+            return Err(ConbhuideError::Parse("Not a saved board".to_string()));
+        }
+        let version = bytes[BYTES_MAGIC.len()];
+        if version != BYTES_VERSION {
+            return Err(ConbhuideError::Parse(format!(
+                "Saved board is version {}, but only version {} can be read",
+                version, BYTES_VERSION
+            )));
+        }
+        let width = u16::from_le_bytes([bytes[header_len - 4], bytes[header_len - 3]]);
+        let height = u16::from_le_bytes([bytes[header_len - 2], bytes[header_len - 1]]);
+        let n_cells = width as usize * height as usize;
+        let packed = &bytes[header_len..];
+        if packed.len() != n_cells.div_ceil(8) {
+            return Err(ConbhuideError::Parse(format!(
+                "Saved {}x{} board should have {} bytes of cells, not {}",
+                width,
+                height,
+                n_cells.div_ceil(8),
+                packed.len()
+            )));
+        }
+        let cells = (0..n_cells).map(|i| packed[i / 8] & (0x80 >> (i % 8)) != 0).collect();
+        Self::from_cells(width, height, cells, cell_size, frame_top_left)
+    }
+
+    pub fn to_art(&self) -> String {
+        /* the whole board as text, one line per row, with # for a live cell and . for a dead one */
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| if self.cell_is_alive(x, y) { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[cfg(any(test, feature = "testing"))]
+    pub fn assert_equals_art(&self, art: &str) {
+        /* panic unless the board matches art, drawn as in to_art. Blank lines and indentation
+         * are ignored, so art can be written out in an indented string, e.g. a blinker after
+         * one step:
+         *   board.assert_equals_art("
+         *       .....
+         *       ..#..
+         *       ..#..
+         *       ..#..
+         *       .....");
+         * On a mismatch the two boards are printed side by side, with the rows that differ
+         * marked */
+        let expected: Vec<&str> = art.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        let actual = self.to_art();
+        let actual: Vec<&str> = actual.lines().collect();
+        if expected == actual {
+            return;
+        }
+        let rows = expected.len().max(actual.len());
+        let width = expected.iter().map(|line| line.len()).max().unwrap_or(0);
+        let diff: Vec<String> = (0..rows)
+            .map(|row| {
+                let (want, got) = (expected.get(row).copied().unwrap_or(""), actual.get(row).copied().unwrap_or(""));
+                let marker = if want == got { ' ' } else { '!' };
+                format!("{} {:width$}   {}", marker, want, got, width = width)
+            })
+            .collect();
+        panic!(
+            "board doesn't match the art at generation {} (expected on the left, actual on the right):\n{}",
+            self.generation,
+            diff.join("\n")
+        );
+    }
+
+    pub fn load_rle(&mut self, rle: &str, fit: FitMode) -> Result<(), ConbhuideError> {
+        /* replace the board with an RLE pattern, placed at the top left.
+         * A pattern too big for the board is handled according to fit:
+         *  - Clip drops anything that doesn't fit
+         *  - Grow enlarges the board to hold it, and shrinks the cells so the board still fits
+         *    the same screen area
+         *  - ScaleDown shrinks it by a whole factor n, just big enough, so that each n x n block
+         *    of the pattern becomes one cell. The cell is alive if the block's top left cell is;
+         *    the rest of the block is ignored, so thin details can vanish and the result won't
+         *    behave like the original */
+        let mut pattern = rle::decode(rle)?;
+        let fits = pattern.width <= self.width && pattern.height <= self.height;
+        match fit {
+            FitMode::Grow if !fits => {
+                let cell_size = self.cell_size;
+                self.resize(self.width.max(pattern.width), self.height.max(pattern.height));
+                self.fit_to_screen(self.screen_size);
+                self.cell_size = self.cell_size.min(cell_size);
+            }
+            FitMode::ScaleDown if !fits => {
+                let scale = pattern
+                    .width
+                    .div_ceil(self.width.max(1))
+                    .max(pattern.height.div_ceil(self.height.max(1)));
+                pattern.cells = pattern
+                    .cells
+                    .into_iter()
+                    .filter(|&(x, y)| x % scale == 0 && y % scale == 0)
+                    .map(|(x, y)| (x / scale, y / scale))
+                    .collect();
+            }
+            _ => {}
+        }
+        self.restart_generations();
+        self.cells.iter_mut().for_each(|cell| *cell = false);
+        for (x, y) in pattern.cells {
+            if x < self.width && y < self.height {
+                let ind = self.ind_for_pos(x, y);
+                self.cells[ind] = true;
+            }
+        }
+        self.kill_walled_cells();
+        self.recolor();
+        Ok(())
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn restart_generations(&mut self) {
+        /* count from generation 0 again, forgetting the recorded ones */
+        self.generation = 0;
+        self.recorded.clear();
+        self.ages.iter_mut().for_each(|age| *age = 0);
+    }
+
+    pub fn age_at(&self, x: u16, y: u16) -> u16 {
+        /* generations a live cell has survived since it was born */
+        self.ages[self.ind_for_pos(x, y)]
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        /* go back to the generation before this one, if it was recorded (see record_limit).
+         * Any edits made since it was recorded are lost. Returns false, and leaves the board
+         * alone, if there's nothing to go back to */
+        let (cells, colors, ages) = match self.recorded.pop_back() {
+            Some(recorded) => recorded,
+            None => return false,
+        };
+        // nothing to fade from
+        self.previous_cells = cells.clone();
+        self.previous_colors = colors.clone();
+        self.cells = cells;
+        self.colors = colors;
+        // or persistence would count from the ages of the generation stepped back from
+        self.ages = ages;
+        // walls may have gone up since
+        self.kill_walled_cells();
+        self.generation = self.generation.saturating_sub(1);
+        // the stamps fired since are undone with the rest, so let them fire again
+        let generation = self.generation;
+        for emitter in self.emitters.iter_mut().filter(|emitter| emitter.last_fired_gen > generation) {
+            emitter.last_fired_gen = generation - generation % emitter.period;
+        }
+        true
+    }
+
+    pub fn set_on_step(&mut self, f: Box<dyn FnMut(u64, usize)>) {
+        /* have f called at the end of every step with the new generation number and the
+         * population, e.g. to play a sound or stop once the board dies out */
+        self.on_step = Some(f);
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.iter().filter(|&&alive| alive).count()
+    }
+
+    pub fn bounding_box(&self) -> Option<(u16, u16, u16, u16)> {
+        /* (min x, min y, max x, max y) of the live cells, or None for an empty board */
+        let mut bbox: Option<(u16, u16, u16, u16)> = None;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.cell_is_alive(x, y) {
+                    bbox = Some(match bbox {
+                        None => (x, y, x, y),
+                        Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    });
+                }
+            }
+        }
+        bbox
+    }
+
+    pub fn bounding_box_rect(&self) -> Option<Rect> {
+        /* where the bounding box of the live cells is on screen, or None for an empty board */
+        let (x0, y0, x1, y1) = self.bounding_box()?;
+        let top_left = grid_to_screen(x0, y0, self.cell_size, self.frame_top_left);
+        let size = self.cell_size as f32;
+        Some(Rect::new(top_left.x, top_left.y, (x1 - x0 + 1) as f32 * size, (y1 - y0 + 1) as f32 * size))
+    }
+
+    pub fn live_pattern(&self) -> Pattern {
+        /* the live cells, cropped to their bounding box */
+        match self.bounding_box() {
+            None => Pattern {
+                width: 0,
+                height: 0,
+                cells: vec![],
+            },
+            Some((x0, y0, x1, y1)) => Pattern {
+                width: x1 - x0 + 1,
+                height: y1 - y0 + 1,
+                cells: (y0..=y1)
+                    .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+                    .filter(|&(x, y)| self.cell_is_alive(x, y))
+                    .map(|(x, y)| (x - x0, y - y0))
+                    .collect(),
+            },
+        }
+    }
+
+    pub fn fingerprint(&self) -> u64 {
+        /* hash of the live cells relative to their bounding box, so the same pattern anywhere on
+         * the board (or on any board) hashes the same. Different patterns can collide, if rarely */
+        let pattern = self.live_pattern();
+        let cells = pattern.cells.iter().copied();
+        std::iter::once((pattern.width, pattern.height))
+            .chain(cells)
+            .flat_map(|(x, y)| [x.to_le_bytes(), y.to_le_bytes()])
+            .flatten()
+            .fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+    }
+
+    pub fn run_until_stable(&mut self, max_generations: usize) -> Option<Cycle> {
+        /* step until the board repeats an earlier generation, allowing for it having moved.
+         * returns None if that hasn't happened after max_generations steps */
+        let mut watch = CycleWatch::new(max_generations);
+        for generation in 0..=max_generations {
+            if let Some(cycle) = watch.observe(self) {
+                return Some(cycle);
+            }
+            if generation < max_generations {
+                self.step();
+            }
+        }
+        None
+    }
+
+    pub fn step_n(&mut self, n: usize) {
+        /* evolve the matrix n steps, adding each generation to the exposure */
+        for _ in 0..n {
+            self.step();
+            for (exposure, &alive) in self.exposure.iter_mut().zip(&self.cells) {
+                if alive {
+                    *exposure = exposure.saturating_add(1);
+                }
+            }
+        }
+    }
+
+    pub fn exposure_at(&self, x: u16, y: u16) -> u16 {
+        /* generations a cell has been alive for through step_n since the exposure was cleared */
+        self.exposure[self.ind_for_pos(x, y)]
+    }
+
+    pub fn clear_exposure(&mut self) {
+        self.exposure.iter_mut().for_each(|exposure| *exposure = 0);
+    }
+
+    pub fn render_exposure(&self) -> (Vec<u8>, u32, u32) {
+        /* RGBA pixels of a "long exposure" of the board: each cell is shaded from dead_color,
+         * for never alive, to alive_color, for alive the most of any cell */
+        let size = self.cell_size as usize;
+        let (width, height) = (self.width as usize * size, self.height as usize * size);
+        let brightest = self.exposure.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for y in 0..self.height {
+            let row: Vec<[u8; 4]> = (0..self.width)
+                .map(|x| {
+                    let fraction = self.exposure_at(x, y) as f32 / brightest;
+                    let mix = |from: f32, to: f32| from + (to - from) * fraction;
+                    let (dead, alive) = (self.dead_color, self.alive_color);
+                    Color::new(mix(dead.r, alive.r), mix(dead.g, alive.g), mix(dead.b, alive.b), mix(dead.a, alive.a))
+                        .into()
+                })
+                .collect();
+            for _ in 0..size {
+                for pixel in &row {
+                    for _ in 0..size {
+                        pixels.extend_from_slice(pixel);
+                    }
+                }
+            }
+        }
+        (pixels, width as u32, height as u32)
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            generation: self.generation,
+            population: self.population(),
+            cells: self.cells.clone(),
+        }
+    }
+
+    pub fn generations(mut self) -> impl Iterator<Item = Snapshot> {
+        /* the board as it is now, then after each further step, for as long as it's asked for.
+         * Each item copies the cells (a byte per cell); the board itself is stepped in place
+         * rather than cloned, and nothing is stepped until an item is asked for. Snapshots are
+         * detached from the board, so they can be kept, compared or collected freely */
+        let mut started = false;
+        std::iter::from_fn(move || {
+            if started {
+                self.step();
+            }
+            started = true;
+            Some(self.snapshot())
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn bench_steps(&mut self, n: usize) -> Duration {
+        /* wall time taken to evolve n steps, independent of any frame timing */
+        let start = Instant::now();
+        self.step_n(n);
+        start.elapsed()
+    }
+
+    pub fn step_diff(&mut self) -> Vec<(u16, u16)> {
+        /* evolve one step and return the cells that were born or died.
+         * NB macroquad clears the screen every frame, so redrawing just these
+         * only helps when drawing onto a persistent render target */
+        let before = self.cells.clone();
+        self.step();
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| before[self.ind_for_pos(x, y)] != self.cell_is_alive(x, y))
+            .collect()
+    }
+
+    fn fire_emitters(&mut self) {
+        /* stamp the pattern of every emitter due at this generation */
+        let generation = self.generation;
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for emitter in emitters.iter_mut() {
+            if generation % emitter.period == 0 && emitter.last_fired_gen != generation {
+                self.stamp(&emitter.pattern, emitter.pos.0, emitter.pos.1);
+                emitter.last_fired_gen = generation;
+            }
+        }
+        self.emitters = emitters;
+    }
+
     pub fn step(&mut self) {
         /* evolve the matrix one step */
+        let stopwatch = self.profile.then(Stopwatch::start);
         let mut buffer = self.cells.to_vec();
-        for y in 0..self.height as i32 {
-            for x in 0..self.width as i32 {
+        let mut color_buffer = self.colors.to_vec();
+        let mut age_buffer = vec![0u16; self.ages.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
                 let mut n_neighbors = 0;
-                // iterate of cell neighbors
-                for j in -1i32..=1 {
-                    for i in -1i32..=1 {
-                        // out of bounds
-                        if y + j < 0
-                            || y + j >= self.height as i32
-                            || x + i < 0
-                            || x + i >= self.width as i32
-                        {
-                            continue;
-                        }
-                        // I am not a neighbor of myself
-                        if i == 0 && j == 0 {
-                            continue;
-                        }
-
-                        //let neighbor = [(y + j) as usize * w + (x + i) as usize];
-                        //TODO: find a way to take a 2d slice of this 1d vector and sum it rather
-                        //than iterating over each point. Rust must have a better matrix library
-                        if self.cell_is_alive((x + i) as u16, (y + j) as u16) {
-                            n_neighbors += 1;
-                        }
+                // colors of the first three live neighbors, for QuadLife births
+                let mut parent_colors = [0u8; 3];
+                //TODO: find a way to take a 2d slice of this 1d vector and sum it rather
+                //than iterating over each point. Rust must have a better matrix library
+                for (n_x, n_y) in self.live_neighbor_positions(x, y) {
+                    if n_neighbors < 3 {
+                        parent_colors[n_neighbors] = self.colors[self.ind_for_pos(n_x, n_y)];
                     }
+                    n_neighbors += 1;
                 }
 
                 // add new cell state to buffer
-                buffer[self.ind_for_pos(x as u16, y as u16)] =
-                    match (self.cell_is_alive(x as u16, y as u16), n_neighbors) {
-                        (true, x) if x < 2 => false, // Rule 1: live cell with < 2 neighbors dies
-                        (true, 2) | (true, 3) => true, // Rule 2: live cell with 2-3 neighbors survives
-                        (true, x) if x > 3 => false,   // Rule 3: live cell with >3 neighbors dies
-                        (false, 3) => true, // Rule 4: dead cell with 3 neighbors becomes alive
-                        (otherwise, _) => otherwise, // remain in same state
+                let ind = self.ind_for_pos(x, y);
+                let was_alive = self.cell_is_alive(x, y);
+                let aged = was_alive && self.persistence.is_some_and(|persistence| self.ages[ind] >= persistence);
+                let next_alive = match &self.weighted_rule {
+                    Some(weighted_rule) => weighted_rule.next_state(was_alive, self.weighted_neighbors(x, y)),
+                    None => {
+                        self.rule.next_state(was_alive, n_neighbors)
+                            || (aged && n_neighbors > 0 && self.rule.next_state(true, n_neighbors - 1))
+                    }
+                };
+                buffer[ind] = !self.walls[ind] && next_alive;
+                if was_alive && buffer[ind] {
+                    age_buffer[ind] = self.ages[ind].saturating_add(1);
+                }
+                if self.variant == LifeVariant::QuadLife {
+                    color_buffer[ind] = match (was_alive, buffer[ind]) {
+                        (_, false) => 0,
+                        (true, true) => self.colors[ind],
+                        (false, true) => quadlife_birth_color(parent_colors),
                     };
+                }
+            }
+        }
+        self.previous_cells = std::mem::replace(&mut self.cells, buffer);
+        self.previous_colors = std::mem::replace(&mut self.colors, color_buffer);
+        let previous_ages = std::mem::replace(&mut self.ages, age_buffer);
+        self.generation += 1;
+        self.fire_emitters();
+        if self.record_limit > 0 {
+            self.recorded.push_back((self.previous_cells.clone(), self.previous_colors.clone(), previous_ages));
+            while self.recorded.len() > self.record_limit {
+                self.recorded.pop_front();
+            }
+        }
+        if let Some(stopwatch) = stopwatch {
+            self.last_step_us = stopwatch.elapsed_us();
+        }
+        if self.on_step.is_some() {
+            let population = self.population();
+            if let Some(on_step) = self.on_step.as_mut() {
+                on_step(self.generation, population);
             }
         }
-        self.cells = buffer;
     }
 }
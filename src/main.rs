@@ -1,25 +1,368 @@
 /*
  * Main file for compiling to wasm
  */
-pub mod celtic;
-pub mod edge;
-pub mod life;
-use crate::edge::TileMatrix;
-use crate::life::CellMatrix;
-use macroquad::input;
+use conbhuide::app::{AppState, Command, DrawMode};
+#[cfg(feature = "embedded-texture")]
+use conbhuide::celtic::embedded_texture;
+use conbhuide::coords::{parse_grid_pos, physical_to_logical};
+use conbhuide::clock::{StepClock, RATE_RANGE};
+use conbhuide::cycle::CycleWatch;
+use conbhuide::edge::{EditMode, TileMatrix};
+#[cfg(not(feature = "embedded-texture"))]
+use conbhuide::error::ConbhuideError;
+use conbhuide::life::{BoundaryMode, CellMatrix, CellShape, Cycle, Dimensions, Emitter, LifeVariant, PaintMode, SymmetryAxis};
+use conbhuide::patterns::named_pattern;
+use conbhuide::rule::EdgeRule;
+use conbhuide::startup::parse_startup;
+use conbhuide::view::View;
 use macroquad::prelude::*;
-use macroquad::ui::{hash, root_ui, widgets::Window, widgets::Button};
-use std::time;
+use macroquad::ui::{hash, root_ui};
 
 const CELL_SIZE: u16 = 25;
 const FRAME_TOP_LEFT: Vec2 = vec2(0., 40.);
+// tints for strands going over and under at crossings, leaving over as drawn and darkening under
+const WEAVE_COLORS: (Color, Color) = (WHITE, GRAY);
+// a touch held at least this long is treated like a right click
+const LONG_PRESS_SECS: f64 = 0.5;
+// how fast the arrow keys scroll the view, in pixels per second
+const PAN_SPEED: f32 = 400.0;
+// generations kept for stepping back with comma
+const RECORDED_GENERATIONS: usize = 200;
+// generations the cycle watch looks back over, i.e. the longest period it notices
+const CYCLE_WINDOW: usize = 64;
+// most bars in the strand length histogram
+const HISTOGRAM_BINS: usize = 16;
+// length of the dashes round a wrapping board
+const WRAP_DASH: f32 = 8.0;
+// generations between gliders from a stamp gun placed with period
+const EMITTER_PERIOD: u64 = 30;
 
-#[macroquad::main("Conbhuide")]
-async fn main() {
-    let texture: Texture2D = load_texture("img/knots.png").await.unwrap();
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ViewMode {
+    LifeOnly,
+    KnotOnly,
+    // the knot drawn over the life board
+    Overlay,
+    // the life board on the left half of the window and the knot on the right
+    SideBySide,
+}
+
+impl ViewMode {
+    fn next(self) -> Self {
+        match self {
+            ViewMode::LifeOnly => ViewMode::KnotOnly,
+            ViewMode::KnotOnly => ViewMode::Overlay,
+            ViewMode::Overlay => ViewMode::SideBySide,
+            ViewMode::SideBySide => ViewMode::LifeOnly,
+        }
+    }
+
+    fn with_interactive(self, knot: bool) -> Self {
+        /* a single view switches to whichever board takes input; the others show both anyway */
+        match self {
+            ViewMode::LifeOnly | ViewMode::KnotOnly if knot => ViewMode::KnotOnly,
+            ViewMode::LifeOnly | ViewMode::KnotOnly => ViewMode::LifeOnly,
+            view_mode => view_mode,
+        }
+    }
+
+    fn shows(self) -> (bool, bool) {
+        /* whether the life board and the knot are drawn */
+        match self {
+            ViewMode::LifeOnly => (true, false),
+            ViewMode::KnotOnly => (false, true),
+            ViewMode::Overlay | ViewMode::SideBySide => (true, true),
+        }
+    }
+
+    fn knot_offset(self) -> Vec2 {
+        /* how far across the screen the knot is moved from where it would be on its own */
+        match self {
+            ViewMode::SideBySide => vec2(screen_width() / 2.0, 0.0),
+            _ => Vec2::ZERO,
+        }
+    }
+
+    fn in_view(self, screen_pos: Vec2, knot: bool) -> bool {
+        /* whether a point on the screen is over the life board (or the knot), rather than the
+         * toolbar or the other board */
+        let below_toolbar = (screen_pos.x > FRAME_TOP_LEFT.x) & (screen_pos.y > FRAME_TOP_LEFT.y);
+        match self {
+            ViewMode::SideBySide => below_toolbar && (screen_pos.x >= screen_width() / 2.0) == knot,
+            _ => below_toolbar,
+        }
+    }
+}
+
+struct TouchGesture {
+    start_time: f64,
+    start_pos: Vec2,
+    max_touches: usize,
+    // distance between two fingers last frame, while they're both down
+    pinch_distance: Option<f32>,
+    // the fingers have moved apart or together, so this is a pinch rather than a tap
+    pinched: bool,
+}
+
+fn poll_touch_gesture(gesture: &mut Option<TouchGesture>, view: &mut View) -> (Option<Vec2>, Option<Vec2>) {
+    /* track a touch gesture from first contact until every finger is lifted.
+     * returns (primary, secondary) click positions once the gesture completes:
+     * a quick single tap is primary, a two-finger tap or a long press is secondary.
+     * Two fingers moving apart or together zoom the view about the point between them */
+    let touches = touches();
+    let n_down = touches
+        .iter()
+        .filter(|t| !matches!(t.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+        .count();
+    if let Some(first) = touches.iter().find(|t| t.phase == TouchPhase::Started) {
+        let g = gesture.get_or_insert(TouchGesture {
+            start_time: get_time(),
+            // touches come in physical pixels, unlike the mouse
+            start_pos: physical_to_logical(first.position, screen_dpi_scale()),
+            max_touches: 0,
+            pinch_distance: None,
+            pinched: false,
+        });
+        g.max_touches = g.max_touches.max(n_down);
+    }
+    if let Some(g) = gesture.as_mut() {
+        let down: Vec<Vec2> = touches
+            .iter()
+            .filter(|t| !matches!(t.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .map(|t| physical_to_logical(t.position, screen_dpi_scale()))
+            .collect();
+        g.pinch_distance = match down[..] {
+            [a, b] => {
+                let distance = a.distance(b);
+                if let Some(last) = g.pinch_distance.filter(|&last| last > 0.0 && distance != last) {
+                    view.zoom_about(distance / last, (a + b) / 2.0);
+                    g.pinched = true;
+                }
+                Some(distance)
+            }
+            _ => None,
+        };
+    }
+    if n_down > 0 {
+        return (None, None);
+    }
+    match gesture.take() {
+        Some(g) if g.pinched => (None, None),
+        Some(g) if g.max_touches >= 2 || get_time() - g.start_time >= LONG_PRESS_SECS => {
+            (None, Some(g.start_pos))
+        }
+        Some(g) => (Some(g.start_pos), None),
+        None => (None, None),
+    }
+}
+
+fn click_on_board(camera: &Camera2D, screen_pos: Vec2) -> Vec2 {
+    /* where a click lands on the boards. The click handlers work in logical pixels, which is
+     * what macroquad gives the mouse position in; touches need physical_to_logical first */
+    camera.screen_to_world(screen_pos)
+}
+
+fn pan_with_arrow_keys(view: &mut View, grid: &Dimensions) {
+    /* scroll the view while arrow keys are held. Scaling by the frame time keeps the speed
+     * on screen the same whatever the frame rate and zoom, and at least a cell of the grid
+     * always stays in view. With ctrl held the arrows shift the life board instead */
+    let mut direction = Vec2::ZERO;
+    if is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl) {
+        return;
+    }
+    if is_key_down(KeyCode::Left) {
+        direction.x -= 1.0;
+    }
+    if is_key_down(KeyCode::Right) {
+        direction.x += 1.0;
+    }
+    if is_key_down(KeyCode::Up) {
+        direction.y -= 1.0;
+    }
+    if is_key_down(KeyCode::Down) {
+        direction.y += 1.0;
+    }
+    view.pan += direction * PAN_SPEED * get_frame_time() / view.zoom;
+
+    let keep = grid.cell_size as f32;
+    let grid_size = vec2(grid.width as f32, grid.height as f32) * keep;
+    let view_top_left = FRAME_TOP_LEFT;
+    let view_bottom_right = vec2(screen_width(), screen_height());
+    let min = grid.offset + keep - view_bottom_right / view.zoom;
+    let max = grid.offset + grid_size - keep - view_top_left / view.zoom;
+    view.pan = view.pan.clamp(min.min(max), max.max(min));
+}
+
+fn draw_histogram(values: &[usize], bottom_right: Vec2, size: Vec2) {
+    /* bar chart of how often each value comes up, lowest values on the left. Values are
+     * lumped into at most HISTOGRAM_BINS bars, each scaled to the tallest */
+    let max_value = match values.iter().max() {
+        Some(&max_value) => max_value.max(1),
+        None => return,
+    };
+    let n_bins = max_value.min(HISTOGRAM_BINS);
+    let mut counts = vec![0usize; n_bins];
+    for &value in values {
+        // values run from 1 to max_value
+        counts[(value.saturating_sub(1) * n_bins / max_value).min(n_bins - 1)] += 1;
+    }
+    let tallest = *counts.iter().max().unwrap_or(&1) as f32;
+    let top_left = bottom_right - size;
+    draw_rectangle(top_left.x, top_left.y, size.x, size.y, Color::new(1.0, 1.0, 1.0, 0.8));
+    let bar_width = size.x / n_bins as f32;
+    for (bin, &count) in counts.iter().enumerate() {
+        let bar_height = size.y * count as f32 / tallest;
+        draw_rectangle(
+            top_left.x + bin as f32 * bar_width,
+            bottom_right.y - bar_height,
+            bar_width - 1.0,
+            bar_height,
+            DARKBLUE,
+        );
+    }
+    draw_text(&format!("strand lengths 1-{}", max_value), top_left.x + 2.0, top_left.y + 12.0, 16.0, BLACK);
+}
+
+fn draw_wrap_border(grid: &Dimensions) {
+    /* a dashed outline round the board, to show its edges join up with the opposite ones */
+    let size = vec2(grid.width as f32, grid.height as f32) * grid.cell_size as f32;
+    let color = Color::new(0.0, 0.5, 1.0, 0.6);
+    let corners = [
+        grid.offset,
+        grid.offset + vec2(size.x, 0.0),
+        grid.offset + size,
+        grid.offset + vec2(0.0, size.y),
+    ];
+    for side in 0..4 {
+        let (start, end) = (corners[side], corners[(side + 1) % 4]);
+        let length = start.distance(end);
+        let n_dashes = (length / (2.0 * WRAP_DASH)).ceil() as usize;
+        for dash in 0..n_dashes {
+            let from = start.lerp(end, dash as f32 * 2.0 * WRAP_DASH / length);
+            let to = start.lerp(end, ((dash as f32 * 2.0 + 1.0) * WRAP_DASH / length).min(1.0));
+            draw_line(from.x, from.y, to.x, to.y, 2.0, color);
+        }
+    }
+}
+
+// hands a file to the browser to download, see js/download.js
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn conbhuide_download(name: *const u8, name_len: usize, bytes: *const u8, bytes_len: usize);
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "web"))]
+extern "C" {
+    fn conbhuide_copy(text: *const u8, text_len: usize);
+}
+
+fn copy_to_clipboard(text: &str) {
+    /* put text on the clipboard, through the browser's clipboard API in a web build */
+    #[cfg(not(all(target_arch = "wasm32", feature = "web")))]
+    miniquad::window::clipboard_set(text);
+    #[cfg(all(target_arch = "wasm32", feature = "web"))]
+    unsafe {
+        conbhuide_copy(text.as_ptr(), text.len());
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn conbhuide_query_len() -> usize;
+    fn conbhuide_query(bytes: *mut u8, bytes_len: usize);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn page_query() -> String {
+    /* the query string of the page the web build is running in, e.g. `?pattern=glider` */
+    let len = unsafe { conbhuide_query_len() };
+    let mut bytes = vec![0u8; len];
+    unsafe {
+        conbhuide_query(bytes.as_mut_ptr(), len);
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn save_file(name: &str, bytes: &[u8]) {
+    /* write a file next to where we were run from, or in the browser offer it as a download */
+    #[cfg(not(target_arch = "wasm32"))]
+    match std::fs::write(name, bytes) {
+        Ok(()) => info!("Saved {}", name),
+        Err(e) => warn!("Couldn't save {}: {}", name, e),
+    }
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        conbhuide_download(name.as_ptr(), name.len(), bytes.as_ptr(), bytes.len());
+    }
+}
+
+fn draw_info_panel(text: &str, pos: Vec2) {
+    /* a few lines of text on a white panel, below and to the right of pos but kept on screen */
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines.iter().map(|line| measure_text(line, None, 16, 1.0).width).fold(0.0, f32::max) + 8.0;
+    let height = lines.len() as f32 * 16.0 + 6.0;
+    let top_left = vec2(
+        (pos.x + 16.0).min(screen_width() - width).max(0.0),
+        (pos.y + 16.0).min(screen_height() - height).max(0.0),
+    );
+    draw_rectangle(top_left.x, top_left.y, width, height, Color::new(1.0, 1.0, 1.0, 0.9));
+    draw_rectangle_lines(top_left.x, top_left.y, width, height, 1.0, DARKGRAY);
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, top_left.x + 4.0, top_left.y + 16.0 * (i + 1) as f32, 16.0, BLACK);
+    }
+}
+
+fn main() {
+    /* `--headless` runs a pattern and prints the result without opening a window (see
+     * headless.rs); anything else opens the app */
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        if args.iter().any(|arg| arg == "--headless") {
+            match conbhuide::headless::run(&args) {
+                Ok(rle) => print!("{}", rle),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+    }
+    macroquad::Window::new("Conbhuide", run_app());
+}
+
+async fn run_app() {
+    #[cfg(feature = "embedded-texture")]
+    let texture: Texture2D = embedded_texture();
+    #[cfg(not(feature = "embedded-texture"))]
+    let texture: Texture2D = match load_texture("img/knots.png").await {
+        Ok(texture) => texture,
+        Err(e) => {
+            error!("{}", ConbhuideError::AssetLoad(format!("Couldn't load img/knots.png: {}", e)));
+            return;
+        }
+    };
     let screen_size = vec2(screen_width(), screen_height()-FRAME_TOP_LEFT.y);
     let mut cell_matrix: CellMatrix = CellMatrix::new(screen_size, CELL_SIZE, Some(FRAME_TOP_LEFT));
     cell_matrix.randomize(None);
+    cell_matrix.record_limit = RECORDED_GENERATIONS;
+    // startup options, e.g. `conbhuide pattern=glider mode=life`, or `?pattern=glider&mode=life`
+    // on the page
+    #[cfg(not(target_arch = "wasm32"))]
+    let query = std::env::args().skip(1).collect::<Vec<_>>().join("&");
+    #[cfg(target_arch = "wasm32")]
+    let query = page_query();
+    let (startup, warnings) = parse_startup(&query);
+    for warning in warnings {
+        warn!("{}", warning);
+    }
+    if let Some(pattern) = startup.pattern.as_deref().and_then(named_pattern) {
+        cell_matrix.clear();
+        let x = cell_matrix.width.saturating_sub(pattern.width) / 2;
+        let y = cell_matrix.height.saturating_sub(pattern.height) / 2;
+        cell_matrix.stamp(&pattern, x, y);
+    }
     info!(
         "{} by {} canvas, for {} by {} cells",
         screen_height(),
@@ -27,33 +370,94 @@ async fn main() {
         cell_matrix.height,
         cell_matrix.width
     );
-    let mut tile_matrix: TileMatrix = TileMatrix::new(screen_size, CELL_SIZE, texture, Some(FRAME_TOP_LEFT));
+    let tile_matrix: TileMatrix = TileMatrix::new(screen_size, CELL_SIZE, texture, Some(FRAME_TOP_LEFT));
     info!("TileMatrix: width {}, height {}",tile_matrix.width, tile_matrix.height);
 
-    let mut running: bool = true;
     let mut show_edges: bool = true;
-    let mut is_conway: bool = true;
-    let mut fps: f32 = 10.0;
-    let mut step_time: f64 = 0.0;
+    let mut strands_only: bool = false;
+    // swap over and under wherever the life board is alive
+    let mut weave_from_life: bool = false;
+    let mut app = AppState::new(cell_matrix, tile_matrix);
+    app.is_conway = startup.celtic.unwrap_or(true);
+    let mut view_mode = ViewMode::LifeOnly.with_interactive(app.is_conway);
+    let mut clock = StepClock::new(10.0, get_time());
+    let mut interpolate: bool = false;
+    let mut show_profiler: bool = false;
+    let mut touch_gesture: Option<TouchGesture> = None;
+    // where a shift-drag to randomize part of the life board, or a ctrl-drag to save it as a
+    // pattern, started
+    let mut region_start: Option<Vec2> = None;
+    let mut saving_region: bool = false;
+    // how far the view has been scrolled from its starting position, and zoomed
+    let mut view = View::default();
+    let mut cycle_watch = CycleWatch::new(CYCLE_WINDOW);
+    // what the life board has settled into, as of the last frame
+    let mut cycle: Option<Cycle> = None;
+    // a cell position being typed in after pressing Enter, e.g. 10,5, to flip it without the mouse
+    let mut cell_entry: Option<String> = None;
     loop {
+        // commands from the toolbar and keys, applied together once the keys have been read
+        let mut commands: Vec<Command> = vec![];
         // setup ui
         if root_ui().button(None, "Celtic") {
-            is_conway = !is_conway;
+            commands.push(Command::SwitchMode);
         };
         if root_ui().button(vec2(50.,0.), ">||") {
-            running = !running;
+            commands.push(Command::TogglePlay);
         };
-        root_ui().slider(hash!(), "FPS", 0.1..30.0, &mut fps);
+        // all the way down means only step by hand, with S
+        let mut rate = clock.slider_rate();
+        root_ui().slider(hash!(), "FPS", 0.0..*RATE_RANGE.end(), &mut rate);
+        clock.set_slider_rate(rate);
+        if app.is_conway {
+            root_ui().slider(hash!(), "Edge density", 0.0..1.0, &mut app.edge_density);
+        } else {
+            root_ui().slider(hash!(), "Density", 0.05..0.95, &mut app.density);
+        }
+        if app.is_conway || is_key_pressed(KeyCode::Escape) {
+            cell_entry = None;
+        }
+        if let Some(entry) = cell_entry.as_mut() {
+            // get_char_pressed hands back the latest character first
+            let mut typed = vec![];
+            while let Some(c) = get_char_pressed() {
+                typed.push(c);
+            }
+            entry.extend(typed.into_iter().rev().filter(|&c| c.is_ascii_digit() || c == ','));
+            if is_key_pressed(KeyCode::Backspace) {
+                entry.pop();
+            }
+            root_ui().label(None, &format!("Flip cell x,y: {}_", entry));
+        }
+        if !app.is_conway && is_key_pressed(KeyCode::Enter) {
+            match cell_entry.take() {
+                None => {
+                    // forget whatever was typed before
+                    clear_input_queue();
+                    cell_entry = Some(String::new());
+                }
+                Some(entry) => match parse_grid_pos(&entry).and_then(|(x, y)| app.cell_matrix.toggle_cell_xy(x, y)) {
+                    Ok(()) => info!("Flipped cell {}", entry),
+                    Err(e) => warn!("{}", e),
+                },
+            }
+        }
+        // keys typed into the cell entry aren't shortcuts
+        let typing = cell_entry.is_some();
+        let key_pressed = |key: KeyCode| !typing && is_key_pressed(key);
+        let shift_down = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let ctrl_down = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
         //clear_background(WHITE);
-        if is_key_pressed(KeyCode::Space) {
-            running = !running;
-            if running {
-                info!("Resumed");
-            } else {
-                info!("Paused");
+        if key_pressed(KeyCode::Space) {
+            commands.push(Command::TogglePlay);
+        }
+        let preset_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5];
+        if let Some(preset) = preset_keys.iter().position(|&key| key_pressed(key)) {
+            if let Some(rate) = clock.use_preset(preset) {
+                info!("Rate: {} generations per second", rate);
             }
         }
-        if is_key_pressed(KeyCode::E) {
+        if key_pressed(KeyCode::E) {
             show_edges = !show_edges;
             if show_edges {
                 info!("Show edges");
@@ -61,48 +465,462 @@ async fn main() {
                 info!("Hide edges");
             }
         }
-        if is_key_pressed(KeyCode::C) {
-            is_conway = !is_conway;
+        if key_pressed(KeyCode::C) {
+            // in the combined views this just moves input to the other board
+            commands.push(Command::SwitchMode);
+        }
+        if key_pressed(KeyCode::Y) {
+            commands.push(Command::NextTheme);
+        }
+        if key_pressed(KeyCode::Z) {
+            commands.push(Command::Undo);
+        }
+        if key_pressed(KeyCode::Backspace) {
+            commands.push(Command::Clear);
+        }
+        if key_pressed(KeyCode::Tab) {
+            view_mode = view_mode.next();
+            app.is_conway = match view_mode {
+                ViewMode::LifeOnly => false,
+                ViewMode::KnotOnly => true,
+                _ => app.is_conway,
+            };
+            info!("View: {:?}", view_mode);
+        }
+        if key_pressed(KeyCode::N) {
+            app.tile_matrix.show_nodes = !app.tile_matrix.show_nodes;
+            info!("Show nodes: {}", app.tile_matrix.show_nodes);
+        }
+        if key_pressed(KeyCode::L) {
+            app.tile_matrix.show_edge_lines = !app.tile_matrix.show_edge_lines;
+            info!("Show edge lines: {}", app.tile_matrix.show_edge_lines);
+        }
+        if key_pressed(KeyCode::V) && shift_down {
+            app.tile_matrix.skip_open = !app.tile_matrix.skip_open;
+            info!("Skip open tiles: {}", app.tile_matrix.skip_open);
+        } else if key_pressed(KeyCode::V) {
+            app.tile_matrix.highlight_invalid = !app.tile_matrix.highlight_invalid;
+            info!("Highlight invalid tiles: {}", app.tile_matrix.highlight_invalid);
+        }
+        if key_pressed(KeyCode::P) && shift_down {
+            // compare draw times with the profiler
+            app.cell_matrix.cache_draws = !app.cell_matrix.cache_draws;
+            info!("Only redraw changed cells: {}", app.cell_matrix.cache_draws);
+        } else if key_pressed(KeyCode::P) {
+            show_profiler = !show_profiler;
+            app.cell_matrix.profile = show_profiler;
+            app.tile_matrix.profile = show_profiler;
+        }
+        if key_pressed(KeyCode::J) {
+            app.tile_matrix.rounded_joints = !app.tile_matrix.rounded_joints;
+            info!("Rounded joints: {}", app.tile_matrix.rounded_joints);
+        }
+
+        // macroquad also reports touches as left clicks (which keeps the ui buttons working),
+        // so ignore those here and let the gesture decide what a touch means
+        let is_touching = !touches().is_empty();
+        let (touch_primary, touch_secondary) = poll_touch_gesture(&mut touch_gesture, &mut view);
+        let primary_click: Option<Vec2> = if is_mouse_button_pressed(MouseButton::Left) && !is_touching {
+            Some(Vec2::from(mouse_position()))
+        } else {
+            touch_primary
+        };
+        let secondary_click: Option<Vec2> = if is_mouse_button_pressed(MouseButton::Right) {
+            Some(Vec2::from(mouse_position()))
+        } else {
+            touch_secondary
+        };
+
+        if show_profiler {
+            // timings are from the previous frame, since this frame hasn't stepped or drawn yet
+            let timings = if app.is_conway {
+                format!("tiles {}us", app.tile_matrix.last_draw_us())
+            } else {
+                format!("step {}us, draw {}us", app.cell_matrix.last_step_us(), app.cell_matrix.last_draw_us())
+            };
+            root_ui().label(None, &format!("FPS {}, {}", get_fps(), timings));
+        }
+        if let (false, Some(axis)) = (app.is_conway, app.cell_matrix.paint_symmetry) {
+            root_ui().label(None, &format!("Mirror: {:?}", axis));
+        }
+        if !app.is_conway {
+            root_ui().label(None, &format!("Boundary: {:?}", app.cell_matrix.boundary));
+        }
+        if !app.is_conway {
+            let walls = if app.cell_matrix.paint_mode == PaintMode::Walls { " walls" } else { "" };
+            root_ui().label(None, &format!("Click: {:?}{}", app.draw_mode, walls));
+        }
+        if let (false, Some(cycle)) = (app.is_conway, cycle) {
+            root_ui().label(None, &cycle.to_string());
+        }
+        if view_mode.shows() == (true, true) {
+            let interactive = if app.is_conway { "knot" } else { "life" };
+            root_ui().label(None, &format!("View: {:?}, editing {}", view_mode, interactive));
+        }
+        if app.is_conway {
+            let layer = if app.tile_matrix.edit_breaks { " breaks" } else { "" };
+            root_ui().label(None, &format!("Edit: {:?}{}", app.tile_matrix.edit_mode, layer));
+            let complexity = app.tile_matrix.complexity();
+            root_ui().label(
+                None,
+                &format!(
+                    "Crossings {}, strands {} ({} loops, {} open ends)",
+                    complexity.crossings, complexity.strands, complexity.loops, complexity.open_ends
+                ),
+            );
+            let n_invalid = app.tile_matrix.invalid_tiles().len();
+            if n_invalid > 0 {
+                root_ui().label(None, &format!("Invalid tiles: {}", n_invalid));
+            }
+        }
+
+        let grid = if app.is_conway { app.tile_matrix.dimensions() } else { app.cell_matrix.dimensions() };
+        pan_with_arrow_keys(&mut view, &grid);
+        let display_rect = view.display_rect(vec2(screen_width(), screen_height()));
+        let life_camera = Camera2D::from_display_rect(display_rect);
+        // the knot's offset is across the screen, so shrinks as the view zooms in
+        let knot_offset = view_mode.knot_offset() / view.zoom;
+        let knot_camera = Camera2D::from_display_rect(Rect {
+            x: display_rect.x - knot_offset.x,
+            y: display_rect.y - knot_offset.y,
+            ..display_rect
+        });
+
+        let (show_life, show_knot) = view_mode.shows();
+        if show_life {
+            if app.running {
+                for _ in 0..clock.tick(get_time()) {
+                    app.cell_matrix.step();
+                }
+                app.reseed_if_dead(get_time());
+            } else {
+                clock.reset(get_time());
+            }
+        } else {
+            // don't owe the life board the generations it misses while hidden
+            clock.reset(get_time());
         }
 
-        if is_conway {
-            if is_mouse_button_pressed(MouseButton::Left) {
-                //cell_matrix.flip_cell(Vec2::from(mouse_position()));
-                let mouse_pos = Vec2::from(mouse_position());
-                if (mouse_pos.x > FRAME_TOP_LEFT.x) & (mouse_pos.y > FRAME_TOP_LEFT.y) {
-                    tile_matrix.flip_edge(mouse_pos);
+        if app.is_conway {
+            if let Some(mouse_pos) = primary_click {
+                if view_mode.in_view(mouse_pos, true) {
+                    app.tile_matrix.flip_edge(click_on_board(&knot_camera, mouse_pos));
                 }
             }
-            if is_mouse_button_pressed(MouseButton::Right) {
-                let mouse_pos = Vec2::from(mouse_position());
-                let (tile_x, tile_y) = tile_matrix.tile_pos_for_click(mouse_pos);
-                info!("clicked on tile {}, {}:\n\t{:?}",tile_x, tile_y, tile_matrix.tile_for_pos(tile_x, tile_y)); 
+            if let Some(mouse_pos) = secondary_click {
+                let (tile_x, tile_y) = app.tile_matrix.tile_pos_for_click(click_on_board(&knot_camera, mouse_pos));
+                info!("clicked on {}", app.tile_matrix.tile_info(tile_x, tile_y));
+            }
+            if key_pressed(KeyCode::R) {
+                commands.push(Command::Randomize);
             }
-            if is_key_pressed(KeyCode::D) {
-                info!("Edges:");
-                for edge in &tile_matrix.edges {
-                    info!("({}, {}), ({}, {})", edge.0.0, edge.0.1, edge.1.0, edge.1.1);
+            if key_pressed(KeyCode::B) {
+                app.tile_matrix.edit_breaks = !app.tile_matrix.edit_breaks;
+                info!("Editing breaks: {}", app.tile_matrix.edit_breaks);
+            }
+            if key_pressed(KeyCode::T) {
+                app.tile_matrix.edit_mode = match app.tile_matrix.edit_mode {
+                    EditMode::Toggle => EditMode::Add,
+                    EditMode::Add => EditMode::Erase,
+                    EditMode::Erase => EditMode::Toggle,
+                };
+                info!("Edit mode: {:?}", app.tile_matrix.edit_mode);
+            }
+            if key_pressed(KeyCode::O) && shift_down {
+                app.tile_matrix.weave_colors = match app.tile_matrix.weave_colors {
+                    None => Some(WEAVE_COLORS),
+                    Some(_) => None,
+                };
+                info!("Weave colors: {:?}", app.tile_matrix.weave_colors);
+            } else if key_pressed(KeyCode::O) {
+                // shift the column parity, swapping over and under throughout the weave
+                app.tile_matrix.parity_shift.1 = !app.tile_matrix.parity_shift.1;
+                info!("Parity shift: {:?}", app.tile_matrix.parity_shift);
+            }
+            if key_pressed(KeyCode::W) {
+                app.tile_matrix.wrap = !app.tile_matrix.wrap;
+                info!("Wrap knot edges: {}", app.tile_matrix.wrap);
+            }
+            if key_pressed(KeyCode::H) {
+                let history = app.tile_matrix.history_json();
+                info!("Edit history:\n{}", history);
+                miniquad::window::clipboard_set(&history);
+            }
+            if key_pressed(KeyCode::D) && shift_down {
+                // load what D saved
+                let json = miniquad::window::clipboard_get().unwrap_or_default();
+                match app.tile_matrix.from_json(&json) {
+                    Ok(()) => info!("Loaded {} edges", app.tile_matrix.edges.len() / 2),
+                    Err(e) => warn!("{}", e),
                 }
+            } else if key_pressed(KeyCode::D) {
+                let json = app.tile_matrix.to_json();
+                info!("Edges:\n{}", json);
+                miniquad::window::clipboard_set(&json);
             }
-            tile_matrix.draw_tiles();
-            if show_edges {
-                tile_matrix.draw_edges();
+            if key_pressed(KeyCode::S) {
+                app.tile_matrix.outline_color = match app.tile_matrix.outline_color {
+                    None => Some(app.theme.palette().secondary),
+                    Some(_) => None,
+                };
+                info!("Strand shadow: {:?}", app.tile_matrix.outline_color);
+            }
+            if key_pressed(KeyCode::F) {
+                weave_from_life = !weave_from_life;
+                info!("Weave follows life: {}", weave_from_life);
+            }
+            if key_pressed(KeyCode::G) {
+                let rule = EdgeRule::conway();
+                app.tile_matrix.step_edges(&rule);
+                info!("Stepped edges by {}: {} edges", rule.name, app.tile_matrix.edges.len() / 2);
+            }
+            if key_pressed(KeyCode::A) {
+                let mut histogram: Vec<(String, usize)> = app.tile_matrix.tile_histogram().into_iter().collect();
+                histogram.sort();
+                info!("Tiles by drawing: {:?}", histogram);
+            }
+            if key_pressed(KeyCode::K) {
+                strands_only = !strands_only;
+                info!("Strands only: {}", strands_only);
+            }
+            if key_pressed(KeyCode::X) {
+                let svg = app.tile_matrix.to_svg();
+                info!("Knot:\n{}", svg);
+                miniquad::window::clipboard_set(&svg);
             }
         } else {
-            if running {
-                if get_time() > (step_time + ((1.0/fps) as f64)) {
-                    cell_matrix.step();
-                    step_time = get_time();
+            if key_pressed(KeyCode::S) {
+                commands.push(Command::Step);
+            }
+            if key_pressed(KeyCode::Comma) {
+                commands.push(Command::Undo);
+            }
+            if key_pressed(KeyCode::G) {
+                app.cell_matrix.color_by_density = !app.cell_matrix.color_by_density;
+                info!("Color by neighbor count: {}", app.cell_matrix.color_by_density);
+            }
+            if key_pressed(KeyCode::I) {
+                interpolate = !interpolate;
+                info!("Fade between generations: {}", interpolate);
+            }
+            if key_pressed(KeyCode::Q) {
+                let variant = match app.cell_matrix.variant() {
+                    LifeVariant::Conway => LifeVariant::QuadLife,
+                    LifeVariant::QuadLife => LifeVariant::Conway,
+                };
+                app.cell_matrix.set_variant(variant);
+                info!("Switched to {:?}", variant);
+            }
+            if key_pressed(KeyCode::M) {
+                app.cell_matrix.paint_symmetry = match app.cell_matrix.paint_symmetry {
+                    None => Some(SymmetryAxis::Horizontal),
+                    Some(SymmetryAxis::Horizontal) => Some(SymmetryAxis::Vertical),
+                    Some(SymmetryAxis::Vertical) => Some(SymmetryAxis::Both),
+                    Some(SymmetryAxis::Both) => None,
+                };
+                info!("Paint symmetry: {:?}", app.cell_matrix.paint_symmetry);
+            }
+            if key_pressed(KeyCode::X) {
+                app.cell_matrix.show_bounding_box = !app.cell_matrix.show_bounding_box;
+                info!("Show bounding box: {}", app.cell_matrix.show_bounding_box);
+            }
+            if key_pressed(KeyCode::O) && shift_down {
+                app.cell_matrix.draw_dead = !app.cell_matrix.draw_dead;
+                info!("Draw dead cells: {}", app.cell_matrix.draw_dead);
+            } else if key_pressed(KeyCode::O) {
+                app.cell_matrix.cell_shape = match app.cell_matrix.cell_shape {
+                    CellShape::Square => CellShape::Circle,
+                    CellShape::Circle => CellShape::Rounded,
+                    CellShape::Rounded => CellShape::Square,
+                };
+                info!("Cell shape: {:?}", app.cell_matrix.cell_shape);
+            }
+            if key_pressed(KeyCode::W) {
+                app.cell_matrix.paint_mode = match app.cell_matrix.paint_mode {
+                    PaintMode::Cells => PaintMode::Walls,
+                    PaintMode::Walls => PaintMode::Cells,
+                };
+                info!("Paint mode: {:?}", app.cell_matrix.paint_mode);
+            }
+            if key_pressed(KeyCode::D) && shift_down {
+                // one pixel per cell, unlike a screenshot
+                match app.cell_matrix.to_png_1bpp() {
+                    Ok(png_bytes) => save_file("board.png", &png_bytes),
+                    Err(e) => warn!("{}", e),
+                }
+            } else if key_pressed(KeyCode::D) {
+                let rle = app.cell_matrix.to_rle();
+                info!("Board:\n{}", rle);
+                copy_to_clipboard(&rle);
+            }
+            if ctrl_down {
+                for &(key, dx, dy) in [(KeyCode::Left, -1, 0), (KeyCode::Right, 1, 0), (KeyCode::Up, 0, -1), (KeyCode::Down, 0, 1)].iter() {
+                    if key_pressed(key) {
+                        app.cell_matrix.shift(dx, dy);
+                    }
                 }
             }
-            if is_mouse_button_pressed(MouseButton::Left) {
-                //cell_matrix.flip_cell(Vec2::from(mouse_position()));
-                let mouse_pos = Vec2::from(mouse_position());
-                if (mouse_pos.x > FRAME_TOP_LEFT.x) & (mouse_pos.y > FRAME_TOP_LEFT.y) {
-                    cell_matrix.flip_cell(mouse_pos);
+            if key_pressed(KeyCode::F) {
+                // frame the live cells in the space below the toolbar
+                let viewport = Rect::new(0.0, FRAME_TOP_LEFT.y, screen_width(), screen_height() - FRAME_TOP_LEFT.y);
+                view.fit_view_to(app.cell_matrix.bounding_box_rect(), viewport);
+                info!("Zoomed to {}", view.zoom);
+            }
+            if key_pressed(KeyCode::B) {
+                app.cell_matrix.boundary = app.cell_matrix.boundary.next();
+                info!("Boundary: {:?}", app.cell_matrix.boundary);
+            }
+            if key_pressed(KeyCode::R) && ctrl_down {
+                app.auto_reseed_on_death = !app.auto_reseed_on_death;
+                info!("Reseed when the board dies out: {}", app.auto_reseed_on_death);
+            } else if key_pressed(KeyCode::R) && shift_down {
+                // a blob in the middle, thinning out to nothing at the edges
+                let center = vec2(app.cell_matrix.width as f32, app.cell_matrix.height as f32) / 2.0;
+                let (radius, density) = (center.min_element().max(1.0), app.density);
+                app.cell_matrix.randomize_with(|x, y| {
+                    let distance = vec2(x as f32 + 0.5, y as f32 + 0.5).distance(center) / radius;
+                    2.0 * density * (1.0 - distance).max(0.0)
+                });
+                info!("Reseeded a blob at density {}", density);
+            } else if key_pressed(KeyCode::R) {
+                commands.push(Command::Randomize);
+            }
+            if let Some(mouse_pos) = primary_click {
+                if view_mode.in_view(mouse_pos, false) {
+                    let board_pos = click_on_board(&life_camera, mouse_pos);
+                    if app.draw_mode == DrawMode::Select || shift_down || ctrl_down {
+                        // drag out a rectangle to save, or with shift to randomize. Shift and
+                        // ctrl are shortcuts for selecting from any mode
+                        region_start = Some(board_pos);
+                        saving_region = !shift_down;
+                    } else if let Some((x, y)) = app.cell_matrix.cell_pos_for_click(board_pos) {
+                        commands.push(Command::ClickCell(x, y));
+                    }
                 }
             }
-            cell_matrix.draw();
+            if let Some(start) = region_start {
+                if is_mouse_button_released(MouseButton::Left) {
+                    let end = click_on_board(&life_camera, Vec2::from(mouse_position()));
+                    if saving_region {
+                        let name = format!("user-{}", app.patterns.patterns.len() + 1);
+                        let pattern = app.cell_matrix.extract_pattern(start, end, &name);
+                        app.patterns.add(&name, pattern);
+                        miniquad::window::clipboard_set(&app.patterns.to_json());
+                    } else {
+                        app.cell_matrix.randomize_region(start, end, Some(app.density));
+                    }
+                    region_start = None;
+                }
+            }
+            if key_pressed(KeyCode::U) && shift_down {
+                app.stamp_orientation = app.stamp_orientation.next();
+                info!("Stamping patterns {:?}", app.stamp_orientation);
+            } else if key_pressed(KeyCode::U) {
+                // stamp the latest saved pattern with its top left under the mouse, whatever the
+                // draw mode
+                let board_pos = click_on_board(&life_camera, Vec2::from(mouse_position()));
+                if let Some((x, y)) = app.cell_matrix.cell_pos_for_click(board_pos) {
+                    app.stamp_latest(x, y);
+                }
+            }
+            if key_pressed(KeyCode::T) {
+                commands.push(Command::SetDrawMode(app.draw_mode.next()));
+            }
+            if key_pressed(KeyCode::Period) && shift_down {
+                info!("Removed {} emitters", app.cell_matrix.emitters.len());
+                app.cell_matrix.emitters.clear();
+            } else if key_pressed(KeyCode::Period) {
+                // a stamp gun under the mouse, sending gliders toward the middle of the board
+                let board_pos = click_on_board(&life_camera, Vec2::from(mouse_position()));
+                if let (Some((x, y)), Some(glider)) = (app.cell_matrix.cell_pos_for_click(board_pos), named_pattern("glider")) {
+                    let glider = glider.flipped(x > app.cell_matrix.width / 2, y > app.cell_matrix.height / 2);
+                    app.cell_matrix.emitters.push(Emitter::new((x, y), glider, EMITTER_PERIOD));
+                    info!("Emitting gliders from {},{} every {} generations", x, y, EMITTER_PERIOD);
+                }
+            }
+        }
+        for command in commands {
+            app.apply(command);
+            if command == Command::SwitchMode {
+                view_mode = view_mode.with_interactive(app.is_conway);
+            }
+        }
+        if show_life {
+            let now_cycle = cycle_watch.observe(&app.cell_matrix).filter(|_| app.cell_matrix.population() > 0);
+            if now_cycle != cycle {
+                if let Some(now_cycle) = now_cycle {
+                    info!("{} from generation {}", now_cycle, app.cell_matrix.generation());
+                }
+                cycle = now_cycle;
+            }
+        }
+
+        // the life board goes underneath, so the knot covers it in the combined views
+        if show_life {
+            set_camera(&life_camera);
+            if interpolate && app.running {
+                app.cell_matrix.draw_blended(clock.fraction(get_time()));
+            } else {
+                app.cell_matrix.draw();
+            }
+            let grid = app.cell_matrix.dimensions();
+            match app.cell_matrix.boundary {
+                BoundaryMode::Wrap => draw_wrap_border(&grid),
+                BoundaryMode::Mirror => {
+                    // a solid edge for the patterns to bounce off
+                    let size = vec2(grid.width as f32, grid.height as f32) * grid.cell_size as f32;
+                    draw_rectangle_lines(grid.offset.x, grid.offset.y, size.x, size.y, 3.0, GRAY);
+                }
+                BoundaryMode::Dead => {}
+            }
+            if let (false, Some(start)) = (app.is_conway, region_start) {
+                let end = click_on_board(&life_camera, Vec2::from(mouse_position()));
+                let (top_left, size) = (start.min(end), (end - start).abs());
+                let color = if saving_region { SKYBLUE } else { ORANGE };
+                draw_rectangle_lines(top_left.x, top_left.y, size.x, size.y, 2.0, color);
+            }
+        }
+        if show_knot {
+            set_camera(&knot_camera);
+            if view_mode == ViewMode::Overlay {
+                // tiles would hide the board completely
+                app.tile_matrix.draw_strands();
+            } else if strands_only {
+                app.tile_matrix.draw_strands_only();
+            } else {
+                app.tile_matrix.draw_tiles_with_life(weave_from_life.then_some(&app.cell_matrix));
+            }
+            if show_edges {
+                app.tile_matrix.draw_edges();
+            }
+            // outline the tile under the cursor, unless it's over the toolbar or off the grid
+            let mouse_pos = Vec2::from(mouse_position());
+            if app.is_conway && view_mode.in_view(mouse_pos, true) {
+                let (tile_x, tile_y) = app.tile_matrix.tile_pos_for_click(click_on_board(&knot_camera, mouse_pos));
+                if tile_x < app.tile_matrix.width && tile_y < app.tile_matrix.height {
+                    let top_left = app.tile_matrix.loc_for_tile(tile_x, tile_y);
+                    let tile_size = app.tile_matrix.dimensions().cell_size as f32;
+                    draw_rectangle_lines(top_left.x, top_left.y, tile_size, tile_size, 2.0, YELLOW);
+                }
+            }
+        }
+        set_default_camera();
+        if show_knot {
+            // drawn after the camera is reset so it stays in the corner while panning
+            draw_histogram(
+                &app.tile_matrix.strand_lengths(),
+                vec2(screen_width() - 10.0, screen_height() - 10.0),
+                vec2(160.0, 80.0),
+            );
+        }
+        // hold alt to see what the tile under the cursor is made of, e.g. in the web build where
+        // the right click log can't be seen
+        let mouse_pos = Vec2::from(mouse_position());
+        if app.is_conway && (is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt)) && view_mode.in_view(mouse_pos, true) {
+            let (tile_x, tile_y) = app.tile_matrix.tile_pos_for_click(click_on_board(&knot_camera, mouse_pos));
+            if tile_x < app.tile_matrix.width && tile_y < app.tile_matrix.height {
+                draw_info_panel(&app.tile_matrix.tile_info(tile_x, tile_y), mouse_pos);
+            }
         }
         next_frame().await
     }
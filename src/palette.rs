@@ -0,0 +1,107 @@
+/*
+ * Color themes shared by the life board and the knot.
+ *
+ * A Palette only says which color plays which part. Applying one copies its colors into the
+ * boards' own color fields, so they can still be tweaked one at a time afterwards.
+ */
+use crate::edge::TileMatrix;
+use crate::life::CellMatrix;
+use macroquad::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Palette {
+    // dead cells, and behind the strands
+    pub background: Color,
+    // live cells and strands
+    pub primary: Color,
+    // walls, and strand shadows when they're on
+    pub secondary: Color,
+    // highlights such as the bounding box
+    pub accent: Color,
+    pub even_node: Color,
+    pub odd_node: Color,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Theme {
+    #[default]
+    Classic,
+    Parchment,
+    Neon,
+    Monochrome,
+}
+
+impl Theme {
+    pub fn next(self) -> Self {
+        match self {
+            Theme::Classic => Theme::Parchment,
+            Theme::Parchment => Theme::Neon,
+            Theme::Neon => Theme::Monochrome,
+            Theme::Monochrome => Theme::Classic,
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            // the colors the boards start with
+            Theme::Classic => Palette {
+                background: WHITE,
+                primary: BLACK,
+                secondary: DARKGRAY,
+                accent: MAGENTA,
+                even_node: RED,
+                odd_node: BLUE,
+            },
+            Theme::Parchment => Palette {
+                background: Color::new(0.96, 0.91, 0.78, 1.0),
+                primary: Color::new(0.35, 0.2, 0.08, 1.0),
+                secondary: Color::new(0.6, 0.5, 0.35, 1.0),
+                accent: Color::new(0.55, 0.1, 0.1, 1.0),
+                even_node: Color::new(0.7, 0.3, 0.1, 1.0),
+                odd_node: Color::new(0.2, 0.35, 0.3, 1.0),
+            },
+            Theme::Neon => Palette {
+                background: Color::new(0.05, 0.02, 0.1, 1.0),
+                primary: Color::new(0.2, 1.0, 0.6, 1.0),
+                secondary: Color::new(0.3, 0.2, 0.5, 1.0),
+                accent: Color::new(1.0, 0.2, 0.8, 1.0),
+                even_node: Color::new(1.0, 0.9, 0.1, 1.0),
+                odd_node: Color::new(0.1, 0.8, 1.0, 1.0),
+            },
+            Theme::Monochrome => Palette {
+                background: WHITE,
+                primary: BLACK,
+                secondary: GRAY,
+                accent: DARKGRAY,
+                even_node: DARKGRAY,
+                odd_node: GRAY,
+            },
+        }
+    }
+}
+
+impl CellMatrix {
+    pub fn apply_palette(&mut self, palette: &Palette) {
+        self.dead_color = palette.background;
+        self.alive_color = palette.primary;
+        self.wall_color = palette.secondary;
+        self.bounding_box_color = palette.accent;
+    }
+}
+
+impl TileMatrix {
+    pub fn apply_palette(&mut self, palette: &Palette) {
+        /* the tile drawings come from the texture, so only the strand drawings (see vector.rs),
+         * nodes and edges change color */
+        self.strand_background = palette.background;
+        self.strand_color = palette.primary;
+        // edges are drawn over the tiles, which are mostly strand colored
+        self.edge_color = palette.background;
+        self.break_color = palette.accent;
+        if self.outline_color.is_some() {
+            self.outline_color = Some(palette.secondary);
+        }
+        self.even_node_color = palette.even_node;
+        self.odd_node_color = palette.odd_node;
+    }
+}
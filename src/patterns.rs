@@ -0,0 +1,80 @@
+/*
+ * A few well-known Life patterns, by name, for seeding the board, and any more saved from
+ * the board while running.
+ */
+use crate::error::ConbhuideError;
+use crate::rle::{self, Pattern};
+use crate::rule::Rule;
+
+static LIBRARY: [(&str, &str); 6] = [
+    ("glider", "x = 3, y = 3\nbo$2bo$3o!"),
+    ("blinker", "x = 3, y = 1\n3o!"),
+    ("lwss", "x = 5, y = 4\nbo2bo$o$o3bo$4o!"),
+    ("r-pentomino", "x = 3, y = 3\nb2o$2o$bo!"),
+    ("acorn", "x = 7, y = 3\nbo$3bo$2o2b3o!"),
+    (
+        "gosper-gun",
+        "x = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$\
+         2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+];
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    LIBRARY.iter().map(|(name, _)| *name)
+}
+
+pub fn named_pattern(name: &str) -> Option<Pattern> {
+    /* the library pattern called name, ignoring case */
+    LIBRARY
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map(|(_, rle)| rle::decode(rle).unwrap())
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatternRegistry {
+    // patterns saved while running, oldest first
+    pub patterns: Vec<(String, Pattern)>,
+}
+
+impl PatternRegistry {
+    pub fn add(&mut self, name: &str, pattern: Pattern) {
+        /* save a pattern, replacing any saved under the same name */
+        self.patterns.retain(|(known, _)| !known.eq_ignore_ascii_case(name));
+        self.patterns.push((name.to_string(), pattern));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Pattern> {
+        /* the saved pattern called name, ignoring case, or else the library one */
+        self.patterns
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|(_, pattern)| pattern.clone())
+            .or_else(|| named_pattern(name))
+    }
+
+    pub fn latest(&self) -> Option<&(String, Pattern)> {
+        self.patterns.last()
+    }
+
+    pub fn to_json(&self) -> String {
+        /* the saved patterns as a list of [name, RLE] pairs */
+        let encoded: Vec<(&str, String)> = self
+            .patterns
+            .iter()
+            .map(|(name, pattern)| (name.as_str(), rle::encode_pattern(pattern, &Rule::conway())))
+            .collect();
+        serde_json::to_string(&encoded).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ConbhuideError> {
+        /* read patterns saved with to_json */
+        let encoded: Vec<(String, String)> =
+            serde_json::from_str(json).map_err(|e| ConbhuideError::Parse(format!("Bad saved patterns: {}", e)))?;
+        let mut registry = PatternRegistry::default();
+        for (name, rle) in encoded {
+            registry.add(&name, rle::decode(&rle)?);
+        }
+        Ok(registry)
+    }
+}
@@ -0,0 +1,273 @@
+/*
+ * Run-length encoded patterns, the usual format for sharing Life patterns
+ * (see https://conwaylife.com/wiki/Run_Length_Encoded)
+ *
+ *   #C optional comment lines
+ *   x = 3, y = 3, rule = B3/S23
+ *   bob$2bo$3o!
+ *
+ * `b` is a dead cell, `o` a live one, `$` ends a row and `!` ends the pattern.
+ * Any tag can be preceded by a repeat count.
+ */
+use crate::error::ConbhuideError;
+use crate::rule::Rule;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    pub width: u16,
+    pub height: u16,
+    // live cells, relative to the pattern's top left
+    pub cells: Vec<(u16, u16)>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Orientation {
+    /* the eight ways a pattern can be turned over and round: mirrored left to right or not,
+     * then turned clockwise by a number of quarter turns */
+    #[default]
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    Mirror,
+    MirrorRotate90,
+    MirrorRotate180,
+    MirrorRotate270,
+}
+
+impl Orientation {
+    pub const ALL: [Orientation; 8] = [
+        Orientation::Identity,
+        Orientation::Rotate90,
+        Orientation::Rotate180,
+        Orientation::Rotate270,
+        Orientation::Mirror,
+        Orientation::MirrorRotate90,
+        Orientation::MirrorRotate180,
+        Orientation::MirrorRotate270,
+    ];
+
+    pub fn next(self) -> Self {
+        /* cycle through ALL, e.g. from a key */
+        let i = Self::ALL.iter().position(|&orientation| orientation == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn mirrored(self) -> bool {
+        matches!(
+            self,
+            Orientation::Mirror | Orientation::MirrorRotate90 | Orientation::MirrorRotate180 | Orientation::MirrorRotate270
+        )
+    }
+
+    fn quarter_turns(self) -> usize {
+        match self {
+            Orientation::Identity | Orientation::Mirror => 0,
+            Orientation::Rotate90 | Orientation::MirrorRotate90 => 1,
+            Orientation::Rotate180 | Orientation::MirrorRotate180 => 2,
+            Orientation::Rotate270 | Orientation::MirrorRotate270 => 3,
+        }
+    }
+}
+
+impl Pattern {
+    pub fn oriented(&self, orientation: Orientation) -> Pattern {
+        /* the pattern turned to the given orientation, still with its top left at 0,0 */
+        let mut pattern = self.flipped(orientation.mirrored(), false);
+        for _ in 0..orientation.quarter_turns() {
+            // a clockwise quarter turn: the left column becomes the top row
+            let height = pattern.height;
+            let mut cells: Vec<(u16, u16)> = pattern.cells.iter().map(|&(x, y)| (height - 1 - y, x)).collect();
+            cells.sort_by_key(|&(x, y)| (y, x));
+            pattern = Pattern {
+                width: pattern.height,
+                height: pattern.width,
+                cells,
+            };
+        }
+        pattern
+    }
+
+    pub fn flipped(&self, flip_x: bool, flip_y: bool) -> Pattern {
+        /* the pattern mirrored left to right and/or top to bottom, e.g. to send a glider off
+         * in another direction */
+        let mut cells: Vec<(u16, u16)> = self
+            .cells
+            .iter()
+            .map(|&(x, y)| {
+                (
+                    if flip_x { self.width - 1 - x } else { x },
+                    if flip_y { self.height - 1 - y } else { y },
+                )
+            })
+            .collect();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        Pattern {
+            width: self.width,
+            height: self.height,
+            cells,
+        }
+    }
+}
+
+// keep encoded lines at a length other tools are happy with
+const MAX_LINE_LEN: usize = 70;
+
+pub fn encode(width: u16, height: u16, cells: &[bool], rule: &Rule) -> String {
+    /* encode a row-major width x height grid */
+    let mut body = String::new();
+    let mut pending_rows: usize = 0;
+    for y in 0..height as usize {
+        let row = &cells[y * width as usize..(y + 1) * width as usize];
+        // trailing dead cells are implied by the end of the row
+        let row_len = row.iter().rposition(|&alive| alive).map_or(0, |i| i + 1);
+        if row_len > 0 {
+            push_run(&mut body, pending_rows, '$');
+            pending_rows = 0;
+            let mut x = 0;
+            while x < row_len {
+                let run = row[x..row_len].iter().take_while(|&&alive| alive == row[x]).count();
+                push_run(&mut body, run, if row[x] { 'o' } else { 'b' });
+                x += run;
+            }
+        }
+        pending_rows += 1;
+    }
+    body.push('!');
+
+    let mut rle = format!("x = {}, y = {}, rule = {}\n", width, height, rule);
+    let mut line_len = 0;
+    for token in tokens(&body) {
+        if line_len + token.len() > MAX_LINE_LEN {
+            rle.push('\n');
+            line_len = 0;
+        }
+        rle.push_str(token);
+        line_len += token.len();
+    }
+    rle.push('\n');
+    rle
+}
+
+pub fn encode_pattern(pattern: &Pattern, rule: &Rule) -> String {
+    let mut cells = vec![false; pattern.width as usize * pattern.height as usize];
+    for &(x, y) in &pattern.cells {
+        cells[y as usize * pattern.width as usize + x as usize] = true;
+    }
+    encode(pattern.width, pattern.height, &cells, rule)
+}
+
+fn push_run(body: &mut String, run: usize, tag: char) {
+    match run {
+        0 => {}
+        1 => body.push(tag),
+        _ => body.push_str(&format!("{}{}", run, tag)),
+    }
+}
+
+fn tokens(body: &str) -> Vec<&str> {
+    /* split a body into count+tag tokens so lines are only broken between them */
+    let mut tokens = vec![];
+    let mut start = 0;
+    for (i, c) in body.char_indices() {
+        if !c.is_ascii_digit() {
+            tokens.push(&body[start..=i]);
+            start = i + 1;
+        }
+    }
+    tokens
+}
+
+pub fn decode(rle: &str) -> Result<Pattern, ConbhuideError> {
+    let mut lines = rle
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let header = lines.next().ok_or_else(|| ConbhuideError::Parse("RLE has no header line".to_string()))?;
+    let (width, height) = parse_header(header).map_err(ConbhuideError::Parse)?;
+
+    let mut live = vec![];
+    let (mut x, mut y): (u32, u32) = (0, 0);
+    let mut count = String::new();
+    'body: for line in lines {
+        for c in line.chars() {
+            if c.is_ascii_digit() {
+                count.push(c);
+                continue;
+            }
+            let run: u32 = if count.is_empty() {
+                1
+            } else {
+                count.parse().map_err(|_| ConbhuideError::Parse(format!("bad run length '{}'", count)))?
+            };
+            count.clear();
+            match c {
+                'b' | '.' => x += run,
+                'o' | 'A'..='Z' => {
+                    for i in 0..run {
+                        if x + i >= width as u32 || y >= height as u32 {
+                            return Err(ConbhuideError::Parse(format!(
+                                "cell {},{} is outside the {}x{} pattern",
+                                x + i,
+                                y,
+                                width,
+                                height
+                            )));
+                        }
+                        live.push(((x + i) as u16, y as u16));
+                    }
+                    x += run;
+                }
+                '$' => {
+                    y += run;
+                    x = 0;
+                }
+                '!' => break 'body,
+                c if c.is_whitespace() => {}
+                c => return Err(ConbhuideError::Parse(format!("unexpected '{}' in RLE", c))),
+            }
+        }
+    }
+    Ok(Pattern {
+        width,
+        height,
+        cells: live,
+    })
+}
+
+pub fn decode_rule(rle: &str) -> Result<Rule, ConbhuideError> {
+    /* the rule given in an RLE header, or B3/S23 if there isn't one */
+    let header = rle
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .ok_or_else(|| ConbhuideError::Parse("RLE has no header line".to_string()))?;
+    let rule = header.split(',').find_map(|field| {
+        let (key, value) = field.split_once('=')?;
+        (key.trim() == "rule").then(|| value.trim())
+    });
+    rule.map_or(Ok(Rule::default()), Rule::parse)
+}
+
+fn parse_header(header: &str) -> Result<(u16, u16), String> {
+    /* read x and y from e.g. `x = 3, y = 3, rule = B3/S23` */
+    let mut width = None;
+    let mut height = None;
+    for field in header.split(',') {
+        let mut parts = field.splitn(2, '=').map(str::trim);
+        match (parts.next(), parts.next()) {
+            (Some("x"), Some(value)) => {
+                width = Some(value.parse().map_err(|_| format!("bad width '{}'", value))?)
+            }
+            (Some("y"), Some(value)) => {
+                height = Some(value.parse().map_err(|_| format!("bad height '{}'", value))?)
+            }
+            (Some(_), Some(_)) => {} // rule and any other settings
+            _ => return Err(format!("bad RLE header '{}'", header)),
+        }
+    }
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(format!("RLE header '{}' is missing x or y", header)),
+    }
+}
@@ -0,0 +1,49 @@
+/*
+ * Small xorshift random number generator, so a seed gives the same board on every platform
+ * (macroquad::rand makes no such promise, and wasm builds may differ from native ones).
+ */
+use macroquad::miniquad::date;
+
+// xorshift gets stuck at zero, so a zero seed starts from here instead
+const ZERO_SEED_STATE: u64 = 0x9E37_79B9_7F4A_7C15;
+
+#[derive(Clone, Debug)]
+pub struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub fn new(seed: u64) -> Self {
+        XorShift {
+            state: if seed == 0 { ZERO_SEED_STATE } else { seed },
+        }
+    }
+
+    pub fn from_time() -> Self {
+        /* seeded from the clock, for when reproducibility doesn't matter */
+        XorShift::new((date::now() * 1_000_000.0) as u64)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        /* Marsaglia's xorshift64 with shifts 13, 7, 17 */
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    pub fn below(&mut self, n: u32) -> u32 {
+        /* a number from 0 up to but not including n. Always 0 if n is 0 */
+        if n == 0 {
+            return 0;
+        }
+        (self.next_u64() % n as u64) as u32
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        /* a number from 0 up to but not including 1 */
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
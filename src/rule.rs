@@ -0,0 +1,157 @@
+/*
+ * Life-like rules in the usual B/S notation, e.g. B3/S23 for Conway's Game of Life:
+ * a dead cell is born with 3 live neighbors, and a live cell survives with 2 or 3.
+ */
+use crate::error::ConbhuideError;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rule {
+    // indexed by number of live neighbors
+    pub birth: [bool; 9],
+    pub survival: [bool; 9],
+}
+
+impl Rule {
+    pub fn conway() -> Self {
+        Rule::parse("B3/S23").unwrap()
+    }
+
+    pub fn parse(rule: &str) -> Result<Self, ConbhuideError> {
+        /* read B3/S23 style rules. The S part can come first, and case doesn't matter */
+        let mut birth = [false; 9];
+        let mut survival = [false; 9];
+        let mut seen = (false, false);
+        for part in rule.trim().split('/') {
+            let mut chars = part.chars();
+            let counts = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') if !seen.0 => {
+                    seen.0 = true;
+                    &mut birth
+                }
+                Some('S') if !seen.1 => {
+                    seen.1 = true;
+                    &mut survival
+                }
+                _ => return Err(ConbhuideError::Parse(format!("bad rule '{}', expected e.g. B3/S23", rule))),
+            };
+            for c in chars {
+                match c.to_digit(10) {
+                    Some(n) if n <= 8 => counts[n as usize] = true,
+                    _ => return Err(ConbhuideError::Parse(format!("bad neighbor count '{}' in rule '{}'", c, rule))),
+                }
+            }
+        }
+        if seen != (true, true) {
+            return Err(ConbhuideError::Parse(format!("rule '{}' needs both a B and an S part", rule)));
+        }
+        Ok(Rule { birth, survival })
+    }
+
+    pub fn next_state(&self, alive: bool, n_neighbors: usize) -> bool {
+        if alive {
+            self.survival[n_neighbors]
+        } else {
+            self.birth[n_neighbors]
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::conway()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts = |set: &[bool; 9]| -> String {
+            (0..9).filter(|&n| set[n]).map(|n| n.to_string()).collect()
+        };
+        write!(f, "B{}/S{}", counts(&self.birth), counts(&self.survival))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NeighborWeights {
+    // what each of the four cells sharing a side with a cell counts for
+    pub orthogonal: f32,
+    // and each of the four touching it only at a corner
+    pub diagonal: f32,
+}
+
+impl Default for NeighborWeights {
+    fn default() -> Self {
+        /* every neighbor counts for one, which gives plain neighbor counts */
+        NeighborWeights {
+            orthogonal: 1.0,
+            diagonal: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedRule {
+    /* a rule for weighted life, where neighbors are added up by weight and the total is compared
+     * against ranges rather than looked up by count. A dead cell is born if the weighted sum
+     * lies in birth, and a live one survives if it lies in survival, both ends included.
+     * With the default weights, B3/S23 is birth 3.0..=3.0 and survival 2.0..=3.0, and a
+     * range like 2.5..=3.5 allows for half-weighted neighbors */
+    pub weights: NeighborWeights,
+    pub birth: RangeInclusive<f32>,
+    pub survival: RangeInclusive<f32>,
+}
+
+impl WeightedRule {
+    pub fn new(weights: NeighborWeights, birth: RangeInclusive<f32>, survival: RangeInclusive<f32>) -> Self {
+        WeightedRule { weights, birth, survival }
+    }
+
+    pub fn weight(&self, dx: i32, dy: i32) -> f32 {
+        /* what the neighbor at dx,dy from a cell counts for */
+        if dx != 0 && dy != 0 {
+            self.weights.diagonal
+        } else {
+            self.weights.orthogonal
+        }
+    }
+
+    pub fn next_state(&self, alive: bool, weighted_sum: f32) -> bool {
+        if alive {
+            self.survival.contains(&weighted_sum)
+        } else {
+            self.birth.contains(&weighted_sum)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeRule {
+    /* a Life-like rule for the knot's edges (see TileMatrix::step_edges). An edge's neighbors
+     * are the other edges meeting it at either end, from 0 up to 6 of them. An absent edge
+     * appears with exactly birth neighbors, and a present one stays with a number of
+     * neighbors in survival, vanishing if it's lonelier or more crowded than that */
+    pub name: &'static str,
+    pub birth: usize,
+    pub survival: RangeInclusive<usize>,
+}
+
+impl EdgeRule {
+    pub fn conway() -> Self {
+        /* B3/S23, counting edges instead of cells */
+        EdgeRule {
+            name: "conway-edges",
+            birth: 3,
+            survival: 2..=3,
+        }
+    }
+
+    pub fn next_state(&self, present: bool, n_neighbors: usize) -> bool {
+        if present {
+            self.survival.contains(&n_neighbors)
+        } else {
+            n_neighbors == self.birth
+        }
+    }
+}
@@ -0,0 +1,48 @@
+/*
+ * Headless "soup search": seed a small random region, let it settle, and keep anything
+ * interesting it leaves behind (oscillators and spaceships).
+ */
+use crate::life::{CellMatrix, Cycle};
+use crate::rle::encode_pattern;
+use macroquad::rand::gen_range;
+
+// room around the soup for it to spread out before hitting the board edge
+const MARGIN: u16 = 8;
+// give up on soups that haven't settled after this many generations
+const MAX_GENERATIONS: usize = 500;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    /* the settled board, cropped to its live cells */
+    pub rle: String,
+    pub cycle: Cycle,
+}
+
+pub fn soup_search(trials: usize, region: (u16, u16)) -> Vec<Finding> {
+    /* randomize a region x region cells soup `trials` times and report every distinct
+     * settled result with period > 1 or that moves, keyed by its RLE */
+    let (region_w, region_h) = region;
+    let board_w = region_w + 2 * MARGIN;
+    let board_h = region_h + 2 * MARGIN;
+    let mut findings: Vec<Finding> = vec![];
+    for _ in 0..trials {
+        let mut board = CellMatrix::with_dimensions(board_w, board_h, 1, None);
+        for y in MARGIN..MARGIN + region_h {
+            for x in MARGIN..MARGIN + region_w {
+                board.set_cell_xy(x, y, gen_range(0, 2) == 0);
+            }
+        }
+        let cycle = match board.run_until_stable(MAX_GENERATIONS) {
+            Some(cycle) => cycle,
+            None => continue,
+        };
+        if board.population() == 0 || (cycle.period == 1 && cycle.dx == 0 && cycle.dy == 0) {
+            continue;
+        }
+        let rle = encode_pattern(&board.live_pattern(), &board.rule);
+        if !findings.iter().any(|finding| finding.rle == rle) {
+            findings.push(Finding { rle, cycle });
+        }
+    }
+    findings
+}
@@ -0,0 +1,48 @@
+/*
+ * Options for how the app starts, given as key=value pairs: `pattern=glider mode=celtic` on
+ * the command line, or a query string like `?pattern=glider&mode=celtic`.
+ */
+use crate::patterns;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Startup {
+    // library pattern to seed the life board with, rather than a random soup
+    pub pattern: Option<String>,
+    // Some(true) to open in Celtic mode, Some(false) for life; None keeps the default
+    pub celtic: Option<bool>,
+}
+
+pub fn parse_startup(query: &str) -> (Startup, Vec<String>) {
+    /* read options from pairs separated by & or whitespace. Anything unrecognized is
+     * skipped, leaving that option at its default, and explained in the returned warnings */
+    let mut startup = Startup::default();
+    let mut warnings = vec![];
+    let pairs = query
+        .trim_start_matches('?')
+        .split(|c: char| c == '&' || c.is_whitespace())
+        .filter(|pair| !pair.is_empty());
+    for pair in pairs {
+        let (key, value) = match pair.trim_start_matches("--").split_once('=') {
+            Some(key_value) => key_value,
+            None => {
+                warnings.push(format!("Ignoring {}: expected key=value", pair));
+                continue;
+            }
+        };
+        match (key, value.to_ascii_lowercase().as_str()) {
+            ("pattern", name) if patterns::named_pattern(name).is_some() => {
+                startup.pattern = Some(name.to_string());
+            }
+            ("pattern", name) => warnings.push(format!(
+                "Unknown pattern {}, starting with a random board. Try one of: {}",
+                name,
+                patterns::names().collect::<Vec<_>>().join(", ")
+            )),
+            ("mode", "celtic") => startup.celtic = Some(true),
+            ("mode", "life") | ("mode", "conway") => startup.celtic = Some(false),
+            ("mode", mode) => warnings.push(format!("Unknown mode {}, expected celtic or life", mode)),
+            _ => warnings.push(format!("Ignoring unknown option {}", key)),
+        }
+    }
+    (startup, warnings)
+}
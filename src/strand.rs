@@ -0,0 +1,259 @@
+/*
+ * Following strands through a knot.
+ *
+ * Strands run diagonally from one crossing corner to the next (see `TileMatrix::cut_at_corner`),
+ * so every tile carries exactly one piece of strand, joining its top and bottom crossing corners.
+ * At a corner a strand carries straight on through the crossing unless an edge is in the way:
+ * a horizontal edge bounces it back up or down, a vertical one back left or right. A strand
+ * stops where it would leave the grid, or at a corner with both edges through it.
+ *
+ * Where strands do cross, one goes over the other. A properly woven knot alternates over, under,
+ * over along every strand. The tile drawings manage this with a fixed checkerboard: at crossings
+ * in even columns of corners the strand running down and to the right goes over (the other way
+ * round if parity_shift shifts just one of rows and columns).
+ */
+use crate::celtic::Cut;
+use crate::edge::TileMatrix;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Strand {
+    // tiles in the order the strand passes through them
+    pub tiles: Vec<(u16, u16)>,
+    // true if the strand joins back up with itself, false if it has two loose ends
+    pub closed: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ComplexityReport {
+    pub crossings: usize,
+    pub strands: usize,
+    pub loops: usize,
+    pub open_ends: usize,
+}
+
+impl TileMatrix {
+    fn tile_from_corner(&self, corner: (i16, i16), dir: (i16, i16)) -> Option<(u16, u16)> {
+        /* tile reached by leaving a corner diagonally in direction dir, if it's on the grid */
+        let x = corner.0 + dir.0.min(0);
+        let y = corner.1 + dir.1.min(0);
+        if self.wrap {
+            Some((
+                x.rem_euclid(self.width.max(1) as i16) as u16,
+                y.rem_euclid(self.height.max(1) as i16) as u16,
+            ))
+        } else if x < 0 || y < 0 || x >= self.width as i16 || y >= self.height as i16 {
+            None
+        } else {
+            Some((x as u16, y as u16))
+        }
+    }
+
+    fn exit_direction(&self, corner: (i16, i16), dir: (i16, i16)) -> Option<(i16, i16)> {
+        /* direction a strand arriving at corner in direction dir leaves it in */
+        match self.cut_at_corner(corner.0, corner.1) {
+            Cut::Open => Some(dir),
+            Cut::Horizontal => Some((dir.0, -dir.1)),
+            Cut::Vertical => Some((-dir.0, dir.1)),
+            Cut::Cross => None,
+        }
+    }
+
+    fn trace_from(&self, start: (u16, u16), toward_bottom: bool) -> (Vec<(u16, u16)>, bool) {
+        /* tiles after start, following the strand out through its bottom (or top) corner.
+         * returns them and whether the strand came back round to start */
+        let [top, bottom] = Self::crossing_corners(start.0, start.1);
+        let (mut corner, mut dir) = if toward_bottom {
+            (bottom, (bottom.0 - top.0, 1))
+        } else {
+            (top, (top.0 - bottom.0, -1))
+        };
+        let mut tiles = vec![];
+        let max_len = self.width as usize * self.height as usize;
+        while tiles.len() <= max_len {
+            let next = match self
+                .exit_direction(corner, dir)
+                .and_then(|exit| self.tile_from_corner(corner, exit).map(|tile| (tile, exit)))
+            {
+                Some(next) => next,
+                None => return (tiles, false),
+            };
+            let (tile, exit) = next;
+            if tile == start {
+                return (tiles, true);
+            }
+            tiles.push(tile);
+            corner = (corner.0 + exit.0, corner.1 + exit.1);
+            if self.wrap {
+                corner = (
+                    corner.0.rem_euclid(self.width.max(1) as i16),
+                    corner.1.rem_euclid(self.height.max(1) as i16),
+                );
+            }
+            dir = exit;
+        }
+        (tiles, false)
+    }
+
+    pub fn strands(&self) -> Vec<Strand> {
+        /* every strand in the knot. Each tile belongs to exactly one strand */
+        let mut seen = vec![false; self.width as usize * self.height as usize];
+        let mut strands = vec![];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if seen[y as usize * self.width as usize + x as usize] {
+                    continue;
+                }
+                let (forward, closed) = self.trace_from((x, y), true);
+                let mut tiles: Vec<(u16, u16)> = if closed {
+                    vec![]
+                } else {
+                    let (mut backward, _) = self.trace_from((x, y), false);
+                    backward.reverse();
+                    backward
+                };
+                tiles.push((x, y));
+                tiles.extend(forward);
+                for &(t_x, t_y) in &tiles {
+                    seen[t_y as usize * self.width as usize + t_x as usize] = true;
+                }
+                strands.push(Strand { tiles, closed });
+            }
+        }
+        strands
+    }
+
+    fn wrap_corner(&self, corner: (i16, i16)) -> (i16, i16) {
+        if self.wrap {
+            (
+                corner.0.rem_euclid(self.width.max(1) as i16),
+                corner.1.rem_euclid(self.height.max(1) as i16),
+            )
+        } else {
+            corner
+        }
+    }
+
+    fn is_crossing(&self, corner: (i16, i16)) -> bool {
+        /* whether two strands actually cross over each other at a corner */
+        let all_arms = [(1, 1), (1, -1), (-1, 1), (-1, -1)]
+            .iter()
+            .all(|&dir| self.tile_from_corner(corner, dir).is_some());
+        (corner.0 + corner.1) % 2 != 0 && all_arms && matches!(self.cut_at_corner(corner.0, corner.1), Cut::Open)
+    }
+
+    fn strand_crossings(&self, strand: &Strand) -> Vec<((i16, i16), bool)> {
+        /* crossings in the order the strand passes through them, each with whether the strand
+         * is running down and to the right (or back up and to the left) there */
+        let mut steps: Vec<((u16, u16), (u16, u16))> = strand.tiles.windows(2).map(|w| (w[0], w[1])).collect();
+        if strand.closed && strand.tiles.len() > 1 {
+            steps.push((strand.tiles[strand.tiles.len() - 1], strand.tiles[0]));
+        }
+        let mut crossings = vec![];
+        for (from, to) in steps {
+            let to_corners = Self::crossing_corners(to.0, to.1).map(|corner| self.wrap_corner(corner));
+            // the corner a strand passes through between two tiles belongs to both of them
+            let shared = Self::crossing_corners(from.0, from.1)
+                .iter()
+                .copied()
+                .find(|&corner| to_corners.contains(&self.wrap_corner(corner)));
+            if let Some(corner) = shared.filter(|&corner| self.is_crossing(corner)) {
+                let dx = if corner.0 > from.0 as i16 { 1 } else { -1 };
+                let dy = if corner.1 > from.1 as i16 { 1 } else { -1 };
+                crossings.push((self.wrap_corner(corner), dx == dy));
+            }
+        }
+        crossings
+    }
+
+    fn checkerboard_over(&self, corner: (i16, i16)) -> bool {
+        /* whether the strand running down and to the right goes over, as the tiles are drawn */
+        (corner.0 % 2 == 0) != (self.parity_shift.0 != self.parity_shift.1)
+    }
+
+    pub fn over_at(&self, corner: (i16, i16)) -> Option<bool> {
+        /* at a crossing, true if the strand running down and to the right goes over */
+        if !self.is_crossing(corner) {
+            return None;
+        }
+        let corner = self.wrap_corner(corner);
+        Some(*self.weaving.get(&corner).unwrap_or(&self.checkerboard_over(corner)))
+    }
+
+    pub(crate) fn crossing_swapped(&self, x: u16, y: u16) -> Option<bool> {
+        /* None if tile x,y has no crossing at its corners. Otherwise whether it has to be drawn
+         * with over and under swapped from what the checkerboard gives, because the weaving
+         * overrides the crossing there. A tile crossing at both corners can only swap both
+         * together, so its top corner decides */
+        Self::crossing_corners(x, y).iter().find_map(|&corner| {
+            let over = self.over_at(corner)?;
+            Some(over != self.checkerboard_over(self.wrap_corner(corner)))
+        })
+    }
+
+    pub fn fix_weaving(&mut self) -> usize {
+        /* walk every strand and choose over or under at each crossing so that they alternate,
+         * keeping the usual checkerboard wherever it already works. On a grid that tiles
+         * properly the checkerboard always alternates, and nothing is overridden. A crossing
+         * already settled by an earlier strand is kept, so a strand that meets itself out of
+         * step can still miss a beat. The tiles are drawn to match (see crossing_swapped).
+         * Returns how many crossings were overridden */
+        self.weaving.clear();
+        let mut settled: HashMap<(i16, i16), bool> = HashMap::new();
+        for strand in self.strands() {
+            let mut next_over: Option<bool> = None;
+            for (corner, down_right) in self.strand_crossings(&strand) {
+                let over = match settled.get(&corner) {
+                    Some(&down_right_over) => down_right_over == down_right,
+                    None => {
+                        let over = next_over.unwrap_or(self.checkerboard_over(corner) == down_right);
+                        settled.insert(corner, over == down_right);
+                        over
+                    }
+                };
+                next_over = Some(!over);
+            }
+        }
+        self.weaving = settled
+            .into_iter()
+            .filter(|&(corner, down_right_over)| down_right_over != self.checkerboard_over(corner))
+            .collect();
+        self.weaving.len()
+    }
+
+    pub fn crossing_count(&self) -> usize {
+        /* number of corners where two strands actually cross over each other */
+        let mut crossings = 0;
+        // when wrapping, the corners on the far edges are the same as those on the near ones
+        let (max_x, max_y) = if self.wrap {
+            (self.width as i16 - 1, self.height as i16 - 1)
+        } else {
+            (self.width as i16, self.height as i16)
+        };
+        for corner_y in 0..=max_y {
+            for corner_x in 0..=max_x {
+                if self.is_crossing((corner_x, corner_y)) {
+                    crossings += 1;
+                }
+            }
+        }
+        crossings
+    }
+
+    pub fn strand_lengths(&self) -> Vec<usize> {
+        /* how many tiles each strand passes through, in the same order as strands().
+         * Every tile carries one piece of strand, so these add up to width * height */
+        self.strands().iter().map(|strand| strand.tiles.len()).collect()
+    }
+
+    pub fn complexity(&self) -> ComplexityReport {
+        let strands = self.strands();
+        let loops = strands.iter().filter(|strand| strand.closed).count();
+        ComplexityReport {
+            crossings: self.crossing_count(),
+            strands: strands.len(),
+            loops,
+            open_ends: 2 * (strands.len() - loops),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+/*
+ * Drawing the knot as lines rather than textured tiles: each strand becomes a path through
+ * the tiles it passes through, which can be stroked on screen or written out as SVG.
+ *
+ * A path runs through the midpoints between successive tile centers. Where a strand carries
+ * straight on through a crossing that's the crossing corner itself; where it bounces off an
+ * edge it's half a tile short of the corner, which rounds the bend off. Open strands are
+ * extended out to the corners where they end.
+ */
+use crate::edge::TileMatrix;
+use crate::strand::Strand;
+use macroquad::prelude::*;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrandPath {
+    // screen positions
+    pub points: Vec<Vec2>,
+    // true if the last point joins back up to the first
+    pub closed: bool,
+}
+
+fn svg_color(color: Color) -> String {
+    let [r, g, b, _]: [u8; 4] = color.into();
+    format!("rgb({},{},{})", r, g, b)
+}
+
+impl TileMatrix {
+    fn tile_center(&self, tile: (u16, u16)) -> Vec2 {
+        let half = self.dimensions().cell_size as f32 / 2.0;
+        self.loc_for_tile(tile.0, tile.1) + vec2(half, half)
+    }
+
+    fn end_point(&self, end: (u16, u16), next: (u16, u16)) -> Vec2 {
+        /* where an open strand ends: the corner of its end tile facing away from the rest of it */
+        let [top, bottom] = Self::crossing_corners(end.0, end.1);
+        let center = self.tile_center(end);
+        let toward_next = self.tile_center(next) - center;
+        let half = self.dimensions().cell_size as f32 / 2.0;
+        // the corners are half a tile out from the center, diagonally
+        let to_corner = |corner: (i16, i16)| {
+            vec2((corner.0 as f32 - end.0 as f32) * 2.0 - 1.0, (corner.1 as f32 - end.1 as f32) * 2.0 - 1.0) * half
+        };
+        let (a, b) = (to_corner(top), to_corner(bottom));
+        if a.dot(toward_next) < b.dot(toward_next) {
+            center + a
+        } else {
+            center + b
+        }
+    }
+
+    pub fn strand_path(&self, strand: &Strand) -> Vec<StrandPath> {
+        /* the path a strand takes on screen. Usually just one path, but a strand that crosses
+         * the seams of a wrapped grid is split into a piece for each stretch between them */
+        let tiles = &strand.tiles;
+        let n = tiles.len();
+        if n == 0 {
+            return vec![];
+        }
+        let adjacent = |a: (u16, u16), b: (u16, u16)| {
+            (a.0 as i32 - b.0 as i32).abs() <= 1 && (a.1 as i32 - b.1 as i32).abs() <= 1
+        };
+        let midpoint = |a: (u16, u16), b: (u16, u16)| (self.tile_center(a) + self.tile_center(b)) / 2.0;
+        let n_steps = if strand.closed { n } else { n - 1 };
+        // where successive tiles are on opposite sides of a seam
+        let seams: Vec<usize> = (0..n_steps).filter(|&i| !adjacent(tiles[i], tiles[(i + 1) % n])).collect();
+        if strand.closed && seams.is_empty() {
+            let points = (0..n).map(|i| midpoint(tiles[i], tiles[(i + 1) % n])).collect();
+            return vec![StrandPath { points, closed: true }];
+        }
+
+        // break the strand into stretches of tiles without a seam between them
+        let mut stretches: Vec<Vec<(u16, u16)>> = vec![];
+        let start = if strand.closed { seams[0] + 1 } else { 0 };
+        let mut stretch = vec![];
+        for i in 0..n {
+            let index = (start + i) % n;
+            stretch.push(tiles[index]);
+            if seams.contains(&index) {
+                stretches.push(std::mem::take(&mut stretch));
+            }
+        }
+        if !stretch.is_empty() {
+            stretches.push(stretch);
+        }
+
+        stretches
+            .into_iter()
+            .map(|stretch| {
+                let last = stretch.len() - 1;
+                let (first_next, last_prev) = match stretch.len() {
+                    1 => (stretch[0], stretch[0]),
+                    _ => (stretch[1], stretch[last - 1]),
+                };
+                let mut points = vec![];
+                if stretch.len() == 1 {
+                    // a lone tile runs from one of its crossing corners to the other
+                    let [top, bottom] = Self::crossing_corners(stretch[0].0, stretch[0].1);
+                    let origin = self.loc_for_tile(0, 0);
+                    let size = self.dimensions().cell_size as f32;
+                    points.push(origin + vec2(top.0 as f32, top.1 as f32) * size);
+                    points.push(origin + vec2(bottom.0 as f32, bottom.1 as f32) * size);
+                } else {
+                    points.push(self.end_point(stretch[0], first_next));
+                    points.extend(stretch.windows(2).map(|pair| midpoint(pair[0], pair[1])));
+                    points.push(self.end_point(stretch[last], last_prev));
+                }
+                StrandPath { points, closed: false }
+            })
+            .collect()
+    }
+
+    pub fn strand_paths(&self) -> Vec<StrandPath> {
+        self.strands().iter().flat_map(|strand| self.strand_path(strand)).collect()
+    }
+
+    fn draw_path(&self, path: &StrandPath, offset: Vec2, thickness: f32, color: Color) {
+        let n_segments = if path.closed { path.points.len() } else { path.points.len().saturating_sub(1) };
+        for i in 0..n_segments {
+            let (a, b) = (path.points[i] + offset, path.points[(i + 1) % path.points.len()] + offset);
+            draw_line(a.x, a.y, b.x, b.y, thickness, color);
+            // round off the joints between segments
+            draw_circle(b.x, b.y, thickness / 2.0, color);
+        }
+    }
+
+    pub fn draw_strands_only(&self) {
+        /* the strands as plain lines over a solid background, without the tile textures */
+        let dims = self.dimensions();
+        let size = dims.cell_size as f32;
+        draw_rectangle(
+            dims.offset.x,
+            dims.offset.y,
+            dims.width as f32 * size,
+            dims.height as f32 * size,
+            self.strand_background,
+        );
+        self.draw_strands();
+    }
+
+    pub fn draw_strands(&self) {
+        /* just the strand lines, leaving whatever is underneath showing between them */
+        let thickness = self.dimensions().cell_size as f32 / 4.0;
+        let paths = self.strand_paths();
+        // every shadow goes underneath every strand
+        if let Some(outline_color) = self.outline_color {
+            for path in &paths {
+                self.draw_path(path, self.outline_offset, thickness, outline_color);
+            }
+        }
+        for path in &paths {
+            self.draw_path(path, Vec2::ZERO, thickness, self.strand_color);
+        }
+    }
+
+    pub fn to_svg(&self) -> String {
+        /* the strands-only drawing as an SVG document, e.g. for printing */
+        let dims = self.dimensions();
+        let size = dims.cell_size as f32;
+        let (width, height) = (dims.width as f32 * size, dims.height as f32 * size);
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            width, height, width, height
+        );
+        svg.push_str(&format!(
+            "  <rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            width,
+            height,
+            svg_color(self.strand_background)
+        ));
+        let paths = self.strand_paths();
+        // as on screen, shadows first so that the strands are drawn over them
+        let layers = self
+            .outline_color
+            .map(|outline_color| (self.outline_offset, outline_color))
+            .into_iter()
+            .chain(std::iter::once((Vec2::ZERO, self.strand_color)));
+        for (offset, color) in layers {
+            for path in &paths {
+                let points: Vec<String> = path
+                    .points
+                    .iter()
+                    .map(|&point| point + offset - dims.offset)
+                    .map(|point| format!("{},{}", point.x, point.y))
+                    .collect();
+                svg.push_str(&format!(
+                    "  <{} points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\" stroke-linecap=\"round\"/>\n",
+                    if path.closed { "polygon" } else { "polyline" },
+                    points.join(" "),
+                    svg_color(color),
+                    size / 4.0
+                ));
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
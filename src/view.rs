@@ -0,0 +1,65 @@
+/*
+ * Where the boards are looked at from: how far the view has been scrolled and how far it's
+ * zoomed in. main.rs builds its cameras from this every frame.
+ */
+use macroquad::prelude::*;
+use std::ops::RangeInclusive;
+
+// room left round a framed region, as a fraction of the view on each side
+pub const FIT_MARGIN: f32 = 0.05;
+// how far zoom_about will zoom out and in
+pub const ZOOM_RANGE: RangeInclusive<f32> = 0.25..=8.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    // board position shown at the top left of the screen
+    pub pan: Vec2,
+    // screen pixels per board pixel
+    pub zoom: f32,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            pan: Vec2::ZERO,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl View {
+    pub fn display_rect(&self, screen_size: Vec2) -> Rect {
+        /* the part of the boards that fills a screen_size screen, e.g. for
+         * Camera2D::from_display_rect */
+        Rect::new(self.pan.x, self.pan.y, screen_size.x / self.zoom, screen_size.y / self.zoom)
+    }
+
+    pub fn to_screen(&self, board_pos: Vec2) -> Vec2 {
+        /* where a position on the boards appears on the screen */
+        (board_pos - self.pan) * self.zoom
+    }
+
+    pub fn zoom_about(&mut self, factor: f32, screen_pos: Vec2) {
+        /* zoom in by factor (out, if it's below 1), keeping the board position under screen_pos
+         * where it is on the screen. The zoom stays within ZOOM_RANGE */
+        let board_pos = self.pan + screen_pos / self.zoom;
+        self.zoom = (self.zoom * factor).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+        self.pan = board_pos - screen_pos / self.zoom;
+    }
+
+    pub fn fit_view_to(&mut self, bbox: Option<Rect>, viewport: Rect) {
+        /* zoom and scroll so bbox, in board pixels, is centred in viewport, the part of the
+         * screen the boards are seen through, and fills it less FIT_MARGIN all round. Nothing
+         * to frame (e.g. the bounding box of an empty board) goes back to the starting view */
+        let bbox = match bbox {
+            Some(bbox) if bbox.w > 0.0 && bbox.h > 0.0 => bbox,
+            _ => {
+                *self = View::default();
+                return;
+            }
+        };
+        let room = vec2(viewport.w, viewport.h) * (1.0 - 2.0 * FIT_MARGIN);
+        self.zoom = (room.x / bbox.w).min(room.y / bbox.h);
+        self.pan = bbox.center() - viewport.center() / self.zoom;
+    }
+}